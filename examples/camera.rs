@@ -9,7 +9,7 @@ use nalgebra::na::Vec3;
 use nalgebra::na;
 use kiss3d::window;
 use kiss3d::event::KeyReleased;
-use kiss3d::camera::{Camera, ArcBall, FirstPerson};
+use kiss3d::camera::{Camera, ArcBall, FirstPerson, CameraBindings};
 
 #[start]
 fn start(argc: int, argv: **u8) -> int {
@@ -37,7 +37,18 @@ fn main()
                         if key == glfw::Key1 {
                             w.set_camera(arc_ball as @mut Camera)
                         }
-                        else {
+                        else if key == glfw::KeyB {
+                            // switch both cameras to Blender-like inputs
+                            arc_ball.set_bindings(CameraBindings::blender());
+                            first_person.set_bindings(CameraBindings::blender());
+                        }
+                        else if key == glfw::Key3 {
+                            // toggle the arc-ball orthographic projection
+                            let ortho = arc_ball.is_orthographic();
+                            arc_ball.set_orthographic(!ortho);
+                            w.set_camera(arc_ball as @mut Camera)
+                        }
+                        else if key == glfw::Key2 {
                             w.set_camera(first_person as @mut Camera)
                         }
                     }
@@ -46,9 +57,8 @@ fn main()
                 true
             }
 
-            w.draw_line(&na::zero(), &Vec3::x(), &Vec3::x());
-            w.draw_line(&na::zero(), &Vec3::y(), &Vec3::y());
-            w.draw_line(&na::zero(), &Vec3::z(), &Vec3::z());
+            w.draw_axes(&na::zero(), 1.0);
+            w.draw_grid(window::XZPlane, 0.5, 5.0);
 
             let curr_yaw = arc_ball.yaw();
 
@@ -0,0 +1,48 @@
+#[feature(managed_boxes)];
+
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::Vec3;
+use nalgebra::na;
+use kiss3d::window;
+use kiss3d::camera::{Camera, ArcBall, CameraAnimation, CameraPath, CameraState};
+use kiss3d::camera::animation::EaseInOut;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: camera animation") |window| {
+        let mut c = window.add_cube(1.0, 1.0, 1.0);
+        c.set_color(1.0, 0.0, 0.0);
+
+        let camera = @mut ArcBall::new(Vec3::new(0.0f32, 2.0, 10.0), na::zero());
+        window.set_camera(camera as @mut Camera);
+
+        // a looping fly-through around the cube, zooming in on the way
+        let mut path = CameraPath::new();
+        path.add_keyframe(0.0, CameraState::new(Vec3::new(0.0f32, 2.0, 10.0), na::zero()));
+        path.add_keyframe(2.0, CameraState::new(Vec3::new(6.0f32, 4.0, 0.0), na::zero()));
+        path.add_keyframe(4.0, CameraState::new_with_fov(Vec3::new(0.0f32, 1.0, -4.0), na::zero(),
+                                                         30.0f32.to_radians()));
+        path.add_keyframe(6.0, CameraState::new(Vec3::new(-6.0f32, 4.0, 0.0), na::zero()));
+        path.add_keyframe(8.0, CameraState::new_with_fov(Vec3::new(0.0f32, 2.0, 10.0), na::zero(),
+                                                         45.0f32.to_radians()));
+        path.set_easing(EaseInOut);
+        path.set_looping(true);
+
+        let mut animation = CameraAnimation::new(camera as @mut Camera, path);
+        // advance of 1/60s per frame: the motion does not depend on the framerate
+        animation.set_time_step(Some(1.0 / 60.0));
+
+        window.set_camera_animation(Some(animation));
+        window.set_light(window::StickToCamera);
+
+        do window.render_loop |w| {
+            w.draw_grid(window::XZPlane, 1.0, 10.0);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+extern mod kiss3d;
+extern mod nalgebra;
+
+use std::vec;
+use kiss3d::window;
+use kiss3d::resources::textures_manager;
+use kiss3d::resources::textures_manager::RedF32;
+
+static SIZE: uint = 64;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: heatmap") |window| {
+        let mut heat = vec::from_elem(SIZE * SIZE, 0.0f32);
+        let texture  = textures_manager::singleton().add_from_pixels("heatmap", SIZE, SIZE, RedF32, heat);
+
+        let mut quad = window.add_quad(4.0, 4.0, 1, 1);
+        quad.set_texture_slot("diffuse", texture.clone());
+
+        window.set_light(window::StickToCamera);
+
+        let mut t = 0.0f32;
+
+        do window.render_loop |_| {
+            t = t + 0.05;
+
+            for i in range(0u, SIZE) {
+                for j in range(0u, SIZE) {
+                    let x = i as f32 / SIZE as f32 * 6.28;
+                    let y = j as f32 / SIZE as f32 * 6.28;
+
+                    heat[j * SIZE + i] = 0.5 + 0.5 * (x + t).sin() * (y - t).cos();
+                }
+            }
+
+            // stream the new values to the gpu
+            texture.borrow().update_region(0, 0, SIZE, SIZE, RedF32, heat);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::{Vec3, Rotation};
+use kiss3d::window;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: hot reloading") |window| {
+        // edit `media/monkey.obj` or `media/kitten.png` while the example is running.
+        let mut m = window.add_obj("media/monkey.obj", 1.0);
+        m.set_texture("media/kitten.png");
+
+        window.enable_hot_reloading(500);
+        window.set_light(window::StickToCamera);
+
+        do window.render_loop |_| {
+            m.append_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+        }
+    }
+}
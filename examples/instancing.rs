@@ -0,0 +1,43 @@
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::{Vec3, Iso3, Rotation, Translation};
+use nalgebra::na;
+use kiss3d::window;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: instancing") |window| {
+        let mut transforms = ~[];
+        let mut colors     = ~[];
+
+        // a 50x50x50 grid of cubes, drawn with a single draw call
+        for i in range(0u, 50) {
+            for j in range(0u, 50) {
+                for k in range(0u, 50) {
+                    let mut t: Iso3<f32> = na::one();
+                    t.append_translation(&(Vec3::new(i as f32 - 25.0, j as f32 - 25.0, k as f32 - 25.0) * 0.5));
+
+                    transforms.push(t);
+                    colors.push(Vec3::new(i as f32 / 50.0, j as f32 / 50.0, k as f32 / 50.0));
+                }
+            }
+        }
+
+        let mut cubes = window.add_instanced_cubes(0.2, 0.2, 0.2, transforms, colors);
+
+        window.set_light(window::StickToCamera);
+
+        do window.render_loop |_| {
+            do cubes.modify_instances |ts, _| {
+                for t in ts.mut_iter() {
+                    t.prepend_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,35 @@
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::{Vec3, Rotation, Translation};
+use kiss3d::window;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: levels of detail") |window| {
+        let mut monkeys = ~[];
+
+        for i in range(0u, 20) {
+            let mut c = window.add_obj("media/monkey.obj", 1.0);
+
+            // half of the faces from 5 units away, a tenth from 15 units away
+            c.generate_lods([(0.5, 5.0), (0.1, 15.0)]);
+            c.set_color(1.0, 0.0, 0.0);
+            c.append_translation(&Vec3::new(0.0f32, 0.0, i as f32 * 2.5));
+
+            monkeys.push(c);
+        }
+
+        window.set_light(window::StickToCamera);
+
+        do window.render_loop |_| {
+            for c in monkeys.mut_iter() {
+                c.append_rotation(&Vec3::new(0.0f32, 0.014, 0.0))
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::{Vec3, Rotation, Translation};
+use kiss3d::window;
+use kiss3d::obj;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: mesh operations") |window| {
+        // obj files duplicate the vertices lying on texture seams: weld them before processing.
+        let mesh     = obj::parse_file("media/monkey.obj").welded(1.0e-5).unwrap();
+        let coarse   = mesh.decimated(mesh.faces().len() / 10);
+        let smooth   = mesh.subdivided();
+
+        let mut objs = ~[ window.add_mesh(coarse, 1.0),
+                          window.add_mesh(mesh, 1.0),
+                          window.add_mesh(smooth, 1.0) ];
+
+        for (i, o) in objs.mut_iter().enumerate() {
+            o.set_color(1.0, 0.0, 0.0);
+            o.append_translation(&Vec3::new((i as f32 - 1.0) * 2.5, 0.0, 0.0));
+        }
+
+        window.set_light(window::StickToCamera);
+
+        do window.render_loop |_| {
+            for o in objs.mut_iter() {
+                o.append_rotation(&Vec3::new(0.0f32, 0.014, 0.0))
+            }
+        }
+    }
+}
@@ -14,6 +14,8 @@ fn main() {
         let mut c = window.add_obj("media/monkey.obj", 1.0);
 
         c.set_color(1.0, 0.0, 0.0);
+        // place the camera such that the whole mesh is visible
+        window.frame_object(&c);
 
         window.set_light(window::StickToCamera);
 
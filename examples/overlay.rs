@@ -0,0 +1,54 @@
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::{Vec2, Vec3, Rotation};
+use kiss3d::window;
+use kiss3d::event;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: overlay") |window| {
+        let mut c = window.add_cube(1.0, 1.0, 1.0);
+
+        c.set_color(1.0, 0.0, 0.0);
+
+        window.set_light(window::StickToCamera);
+
+        let mut cursor = Vec2::new(0.0f32, 0.0);
+
+        do window.render_loop |w| {
+            do w.poll_events |_, event| {
+                match *event {
+                    event::CursorPos(x, y) => cursor = Vec2::new(x, y),
+                    _ => { }
+                }
+
+                true
+            }
+
+            c.append_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+
+            // a static frame around the window
+            let width  = w.width();
+            let height = w.height();
+            w.draw_rect(&Vec2::new(10.0, 10.0), &Vec2::new(width - 10.0, height - 10.0),
+                        &Vec3::new(1.0, 1.0, 1.0), false);
+
+            // some text on the top-left corner, and a label attached to the cube
+            w.draw_text("Kiss3d overlay\nMove the mouse!", &Vec2::new(20.0, 20.0), 16.0,
+                        &Vec3::new(1.0, 1.0, 1.0));
+            w.draw_label("cube", &Vec3::new(0.0, 0.6, 0.0), 12.0, &Vec3::new(1.0, 0.5, 0.0));
+
+            // a marker following the mouse
+            w.draw_circle(&cursor, 10.0, &Vec3::new(0.0, 1.0, 0.0), true);
+            w.draw_line_2d(&Vec2::new(cursor.x - 20.0, cursor.y), &Vec2::new(cursor.x + 20.0, cursor.y),
+                           &Vec3::new(1.0, 1.0, 0.0));
+            w.draw_line_2d(&Vec2::new(cursor.x, cursor.y - 20.0), &Vec2::new(cursor.x, cursor.y + 20.0),
+                           &Vec3::new(1.0, 1.0, 0.0));
+        }
+    }
+}
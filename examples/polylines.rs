@@ -0,0 +1,40 @@
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::Vec3;
+use kiss3d::window;
+use kiss3d::polyline::{LineStrip, LineLoop, Dashed};
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: polylines") |window| {
+        let square = ~[
+            Vec3::new(-1.0f32, 0.0, -1.0),
+            Vec3::new(1.0, 0.0, -1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(-1.0, 0.0, 1.0)
+        ];
+
+        let mut border = window.add_polyline(square, LineLoop);
+        border.set_color(0.0, 1.0, 0.0);
+        border.set_style(Dashed(0.1, 0.05));
+
+        let mut trail = window.add_polyline(~[], LineStrip);
+        trail.set_color(1.0, 0.0, 0.0);
+        trail.set_width(3.0);
+
+        window.set_light(window::StickToCamera);
+
+        let mut t = 0.0f32;
+
+        do window.render_loop |_| {
+            // the trail grows by one point per frame: only the new segment is uploaded
+            trail.push_point(Vec3::new(t.cos() * 0.8, t * 0.01, t.sin() * 0.8));
+            t = t + 0.05;
+        }
+    }
+}
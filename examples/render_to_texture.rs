@@ -0,0 +1,39 @@
+#[feature(managed_boxes)];
+
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::{Vec3, Rotation, Translation};
+use nalgebra::na;
+use kiss3d::window;
+use kiss3d::camera::{Camera, ArcBall};
+use kiss3d::resources::framebuffers_manager::FramebuffersManager;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: render to texture") |window| {
+        let mut c = window.add_cube(1.0, 1.0, 1.0);
+        c.set_color(1.0, 0.0, 0.0);
+
+        // a monitor displaying the cube seen from above
+        let target      = FramebuffersManager::new_render_target(512, 512);
+        let top_camera  = @mut ArcBall::new(Vec3::new(0.0f32, 5.0, 0.1), na::zero());
+        let mut monitor = window.add_quad(2.0, 2.0, 1, 1);
+        monitor.set_texture_slot("diffuse", target.texture().unwrap());
+        monitor.append_translation(&Vec3::new(2.5f32, 0.0, 0.0));
+        // the monitor must not be rendered into its own texture
+        monitor.set_layers(2);
+
+        window.set_light(window::StickToCamera);
+
+        do window.render_loop |w| {
+            c.append_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+
+            w.render_to_texture(&target, top_camera as @mut Camera, 1);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::{Vec3, Rotation, Translation};
+use kiss3d::window;
+use kiss3d::object;
+use kiss3d::mesh;
+use kiss3d::mesh_ops;
+use kiss3d::obj;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: shading") |window| {
+        // per-pixel flat shading, without modifying the mesh
+        let mut flat = window.add_obj("media/monkey.obj", 1.0);
+        flat.set_shading(object::Flat);
+        flat.append_translation(&Vec3::new(-2.5f32, 0.0, 0.0));
+
+        // smooth shading, except across edges sharper than 30 degrees
+        let mesh      = obj::parse_file("media/monkey.obj").welded(1.0e-5);
+        let creased   = mesh.with_normals(mesh_ops::CreaseNormals(30.0f32.to_radians()), mesh::AngleWeights);
+        let mut crease = window.add_mesh(creased, 1.0);
+
+        let smoothed   = mesh.with_normals(mesh_ops::SmoothNormals, mesh::AreaWeights);
+        let mut smooth = window.add_mesh(smoothed, 1.0);
+        smooth.append_translation(&Vec3::new(2.5f32, 0.0, 0.0));
+
+        window.set_light(window::StickToCamera);
+
+        do window.render_loop |_| {
+            flat.append_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+            crease.append_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+            smooth.append_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+        }
+    }
+}
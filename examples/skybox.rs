@@ -0,0 +1,35 @@
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::{Vec3, Translation};
+use kiss3d::window;
+use kiss3d::resources::textures_manager;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: skybox") |window| {
+        // any image can be used as a panorama, though a 2:1 image covering 360 degrees looks
+        // much better. Use `add_cube_map` to load six separate faces instead.
+        let sky = textures_manager::singleton().add_cube_map_from_panorama("sky", "media/kitten.png", 512);
+
+        window.set_skybox(Some(sky));
+
+        let mut mirror = window.add_sphere(0.5);
+        mirror.set_reflectivity(0.9);
+        mirror.append_translation(&Vec3::new(-0.75f32, 0.0, 0.0));
+
+        let mut matte = window.add_cube(0.75, 0.75, 0.75);
+        matte.set_color(1.0, 0.5, 0.0);
+        matte.set_reflectivity(0.2);
+        matte.append_translation(&Vec3::new(0.75f32, 0.0, 0.0));
+
+        window.set_light(window::StickToCamera);
+
+        do window.render_loop |_| {
+        }
+    }
+}
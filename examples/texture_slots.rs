@@ -0,0 +1,32 @@
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::{Vec3, Rotation, Translation};
+use kiss3d::window;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: texture slots") |window| {
+        // the kitten is only visible on the highlights
+        let mut shiny = window.add_cube(1.0, 1.0, 1.0);
+        shiny.set_texture_slot_from_file("specular", "media/kitten.png");
+        shiny.append_translation(&Vec3::new(-1.0f32, 0.0, 0.0));
+
+        // the kitten glows, even in the dark
+        let mut glowing = window.add_cube(1.0, 1.0, 1.0);
+        glowing.set_color(0.0, 0.0, 0.0);
+        glowing.set_texture_slot_from_file("emissive", "media/kitten.png");
+        glowing.append_translation(&Vec3::new(1.0f32, 0.0, 0.0));
+
+        window.set_light(window::StickToCamera);
+
+        do window.render_loop |_| {
+            shiny.append_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+            glowing.append_rotation(&Vec3::new(0.0f32, 0.014, 0.0));
+        }
+    }
+}
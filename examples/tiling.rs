@@ -0,0 +1,32 @@
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::{Vec3, Rotation};
+use kiss3d::window;
+use kiss3d::resources::textures_manager::TextureParams;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: tiling") |window| {
+        let mut ground = window.add_quad(20.0, 20.0, 1, 1);
+
+        // repeat the kitten 20 times along each axis
+        ground.set_texture_with_params("media/kitten.png", &TextureParams::tiled());
+        ground.set_uv_scale(20.0, 20.0);
+        ground.append_rotation(&Vec3::new(1.57f32, 0.0, 0.0));
+
+        window.set_light(window::StickToCamera);
+
+        let mut offset = 0.0f32;
+
+        do window.render_loop |_| {
+            // scroll the texture
+            offset = offset + 0.001;
+            ground.set_uv_offset(offset, 0.0);
+        }
+    }
+}
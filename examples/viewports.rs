@@ -0,0 +1,50 @@
+#[feature(managed_boxes)];
+
+extern mod kiss3d;
+extern mod nalgebra;
+
+use nalgebra::na::{Vec3, Translation};
+use nalgebra::na;
+use kiss3d::window;
+use kiss3d::camera::{Camera, ArcBall, Orthographic};
+use kiss3d::background::VerticalGradient;
+
+#[start]
+fn start(argc: int, argv: **u8) -> int {
+    std::rt::start_on_main_thread(argc, argv, main)
+}
+
+fn main() {
+    do window::Window::spawn("Kiss3d: viewports") |window| {
+        let mut c = window.add_cube(1.0, 1.0, 1.0);
+        c.set_color(1.0, 0.0, 0.0);
+
+        let mut s = window.add_sphere(0.5);
+        s.set_color(0.0, 1.0, 0.0);
+        s.append_translation(&Vec3::new(1.5f32, 0.0, 0.0));
+
+        // this cone is only shown by the perspective view
+        let mut cone = window.add_cone(1.0, 0.5);
+        cone.set_color(0.0, 0.0, 1.0);
+        cone.append_translation(&Vec3::new(-1.5f32, 0.0, 0.0));
+        cone.set_layers(2);
+
+        let top   = @mut Orthographic::new(Vec3::new(0.0f32, 8.0, 0.0), na::zero(), 5.0);
+        let front = @mut Orthographic::new(Vec3::new(0.0f32, 0.0, 8.0), na::zero(), 5.0);
+        let side  = @mut Orthographic::new(Vec3::new(8.0f32, 0.0, 0.0), na::zero(), 5.0);
+        let persp = @mut ArcBall::new(Vec3::new(5.0f32, 5.0, 5.0), na::zero());
+
+        window.add_viewport(top as @mut Camera, 0.0, 0.0, 0.5, 0.5);
+        window.add_viewport(front as @mut Camera, 0.5, 0.0, 0.5, 0.5);
+        window.add_viewport(side as @mut Camera, 0.0, 0.5, 0.5, 0.5);
+
+        let mut p = window.add_viewport(persp as @mut Camera, 0.5, 0.5, 0.5, 0.5);
+        p.set_layers(1 | 2);
+        p.set_background(Some(VerticalGradient(Vec3::new(0.1f32, 0.1, 0.1), Vec3::new(0.4f32, 0.5, 0.7))));
+
+        window.set_light(window::StickToCamera);
+
+        do window.render_loop |_| {
+        }
+    }
+}
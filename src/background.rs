@@ -0,0 +1,147 @@
+//! Backgrounds drawn behind the scene.
+
+use std::ptr;
+use std::cast;
+use std::mem;
+use std::rc::Rc;
+use gl;
+use gl::types::*;
+use nalgebra::na::Vec3;
+use resources::shaders_manager::BackgroundShaderContext;
+use resources::textures_manager::Texture;
+
+#[path = "error.rs"]
+mod error;
+
+/// The way an image background covers the viewport.
+#[deriving(Eq, Clone, ToStr)]
+pub enum ImageMode {
+    /// The image is stretched to the viewport.
+    Stretch,
+    /// The image is repeated with its original size, starting from the bottom-left corner of
+    /// the viewport. The texture wrap mode must be `Repeat` (see
+    /// `textures_manager::TextureParams`).
+    Tile
+}
+
+/// What is drawn behind the scene. It is covered by the skybox, if any.
+#[deriving(Clone)]
+pub enum Background {
+    /// A uniform color.
+    UniformColor(Vec3<f32>),
+    /// A vertical gradient. The first color is at the bottom of the viewport, the second at the
+    /// top.
+    VerticalGradient(Vec3<f32>, Vec3<f32>),
+    /// A radial gradient. The first color is at the center of the viewport, the second on its
+    /// corners.
+    RadialGradient(Vec3<f32>, Vec3<f32>),
+    /// A 2d image.
+    Image(Rc<Texture>, ImageMode)
+}
+
+impl Background {
+    #[doc(hidden)]
+    // Fails if this background cannot be drawn, i-e. if it is an image with a cube map texture.
+    pub fn check(&self) {
+        match *self {
+            Image(ref t, _) if t.borrow().is_cube_map() =>
+                fail!("A background image must be a 2d texture: use `Window::set_skybox` for cube maps."),
+            _ => { }
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct BackgroundRenderer {
+    priv vbuf: GLuint // screen-covering quad
+}
+
+impl BackgroundRenderer {
+    #[doc(hidden)]
+    pub fn new() -> BackgroundRenderer {
+        let quad: [GLfloat, ..8] = [ -1.0, -1.0,
+                                      1.0, -1.0,
+                                     -1.0,  1.0,
+                                      1.0,  1.0 ];
+        let mut vbuf: GLuint = 0;
+
+        unsafe {
+            verify!(gl::GenBuffers(1, &mut vbuf));
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, vbuf));
+            verify!(gl::BufferData(gl::ARRAY_BUFFER,
+                                   (quad.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                                   cast::transmute(&quad[0]),
+                                   gl::STATIC_DRAW));
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, 0));
+        }
+
+        BackgroundRenderer {
+            vbuf: vbuf
+        }
+    }
+
+    #[doc(hidden)]
+    // Returns true if `background` has to be drawn with the background shader, i-e. if clearing
+    // the screen is not enough.
+    pub fn needs_rendering(&self, background: &Background) -> bool {
+        match *background {
+            UniformColor(_) => false,
+            _               => true
+        }
+    }
+
+    #[doc(hidden)]
+    // Draws the background. The background shader must be selected.
+    pub fn upload(&self, context: &BackgroundShaderContext, background: &Background) {
+        match *background {
+            UniformColor(_) => return,
+            VerticalGradient(ref bottom, ref top) => {
+                verify!(gl::Uniform1i(context.mode, 0));
+                verify!(gl::Uniform3f(context.color1, bottom.x, bottom.y, bottom.z));
+                verify!(gl::Uniform3f(context.color2, top.x, top.y, top.z));
+            },
+            RadialGradient(ref center, ref border) => {
+                verify!(gl::Uniform1i(context.mode, 1));
+                verify!(gl::Uniform3f(context.color1, center.x, center.y, center.z));
+                verify!(gl::Uniform3f(context.color2, border.x, border.y, border.z));
+            },
+            Image(ref texture, mode) => {
+                let (tx, ty) = match mode {
+                    Stretch => (1.0, 1.0),
+                    Tile    => {
+                        let (w, h)   = texture.borrow().size();
+                        let mut viewport = [ 0 as GLint, ..4 ];
+
+                        unsafe { verify!(gl::GetIntegerv(gl::VIEWPORT, &mut viewport[0])); }
+
+                        (viewport[2] as f32 / (w.max(&1) as f32), viewport[3] as f32 / (h.max(&1) as f32))
+                    }
+                };
+
+                verify!(gl::Uniform1i(context.mode, 2));
+                verify!(gl::Uniform2f(context.tiling, tx, ty));
+                verify!(gl::ActiveTexture(gl::TEXTURE0));
+                verify!(gl::BindTexture(gl::TEXTURE_2D, texture.borrow().id()));
+            }
+        }
+
+        verify!(gl::DepthMask(gl::FALSE));
+        verify!(gl::Disable(gl::DEPTH_TEST));
+
+        unsafe {
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, self.vbuf));
+            verify!(gl::VertexAttribPointer(context.pos, 2, gl::FLOAT, gl::FALSE as u8, 0, ptr::null()));
+        }
+        verify!(gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4));
+        verify!(gl::BindBuffer(gl::ARRAY_BUFFER, 0));
+
+        verify!(gl::Enable(gl::DEPTH_TEST));
+        verify!(gl::DepthMask(gl::TRUE));
+    }
+}
+
+impl Drop for BackgroundRenderer {
+    fn drop(&mut self) {
+        unsafe { verify!(gl::DeleteBuffers(1, &self.vbuf)); }
+    }
+}
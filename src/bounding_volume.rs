@@ -0,0 +1,164 @@
+//! Bounding volumes of meshes and objects.
+
+use nalgebra::na::{Vec3, Iso3};
+use nalgebra::na;
+
+/// An axis-aligned bounding box.
+#[deriving(Clone, Eq, ToStr)]
+pub struct AABB {
+    priv mins: Vec3<f32>,
+    priv maxs: Vec3<f32>
+}
+
+impl AABB {
+    /// Creates a new axis-aligned bounding box from its two extremal points.
+    pub fn new(mins: Vec3<f32>, maxs: Vec3<f32>) -> AABB {
+        assert!(mins.x <= maxs.x && mins.y <= maxs.y && mins.z <= maxs.z,
+                "The `mins` of an AABB must be smaller than its `maxs`.");
+
+        AABB {
+            mins: mins,
+            maxs: maxs
+        }
+    }
+
+    /// Computes the smallest axis-aligned bounding box containing a set of points. The bounding
+    /// box of an empty set of points is the origin.
+    pub fn from_points(pts: &[Vec3<f32>]) -> AABB {
+        if pts.is_empty() {
+            return AABB::new(na::zero(), na::zero())
+        }
+
+        let mut mins = pts[0];
+        let mut maxs = pts[0];
+
+        for p in pts.iter() {
+            mins = inf(&mins, p);
+            maxs = sup(&maxs, p);
+        }
+
+        AABB::new(mins, maxs)
+    }
+
+    /// The point with the smallest coordinates of this bounding box.
+    pub fn mins<'r>(&'r self) -> &'r Vec3<f32> {
+        &'r self.mins
+    }
+
+    /// The point with the greatest coordinates of this bounding box.
+    pub fn maxs<'r>(&'r self) -> &'r Vec3<f32> {
+        &'r self.maxs
+    }
+
+    /// The center of this bounding box.
+    pub fn center(&self) -> Vec3<f32> {
+        (self.mins + self.maxs) / 2.0
+    }
+
+    /// The half-extents of this bounding box along each axis.
+    pub fn half_extents(&self) -> Vec3<f32> {
+        (self.maxs - self.mins) / 2.0
+    }
+
+    /// The `i`-th corner of this bounding box, `i` being in `[0, 8[`. Each bit of `i` selects the
+    /// `maxs` (if set) or the `mins` (if not set) coordinate along the `x`, `y` and `z` axis.
+    pub fn corner(&self, i: uint) -> Vec3<f32> {
+        Vec3::new(
+            if i & 1 == 0 { self.mins.x } else { self.maxs.x },
+            if i & 2 == 0 { self.mins.y } else { self.maxs.y },
+            if i & 4 == 0 { self.mins.z } else { self.maxs.z })
+    }
+
+    /// The smallest bounding box containing both `self` and `other`.
+    pub fn merged(&self, other: &AABB) -> AABB {
+        AABB::new(inf(&self.mins, &other.mins), sup(&self.maxs, &other.maxs))
+    }
+
+    /// Tests if a point is inside of this bounding box.
+    pub fn contains_point(&self, p: &Vec3<f32>) -> bool {
+        p.x >= self.mins.x && p.y >= self.mins.y && p.z >= self.mins.z &&
+        p.x <= self.maxs.x && p.y <= self.maxs.y && p.z <= self.maxs.z
+    }
+
+    /// Computes the bounding box of this bounding box once scaled along each axis and then
+    /// transformed by an isometry.
+    pub fn transformed(&self, scale: &Vec3<f32>, transform: &Iso3<f32>) -> AABB {
+        let mut pts = ~[];
+
+        for i in range(0u, 8) {
+            let c = self.corner(i);
+
+            pts.push(na::transform(transform, &Vec3::new(c.x * scale.x, c.y * scale.y, c.z * scale.z)));
+        }
+
+        AABB::from_points(pts)
+    }
+
+    /// The smallest sphere containing this bounding box.
+    pub fn bounding_sphere(&self) -> BoundingSphere {
+        BoundingSphere::new(self.center(), na::norm(&self.half_extents()))
+    }
+}
+
+/// A bounding sphere.
+#[deriving(Clone, Eq, ToStr)]
+pub struct BoundingSphere {
+    priv center: Vec3<f32>,
+    priv radius: f32
+}
+
+impl BoundingSphere {
+    /// Creates a new bounding sphere.
+    pub fn new(center: Vec3<f32>, radius: f32) -> BoundingSphere {
+        assert!(radius >= 0.0, "The radius of a bounding sphere must be positive.");
+
+        BoundingSphere {
+            center: center,
+            radius: radius
+        }
+    }
+
+    /// Computes a bounding sphere of a set of points. The sphere is centered at the center of the
+    /// points bounding box. It is thus not the smallest bounding sphere.
+    pub fn from_points(pts: &[Vec3<f32>]) -> BoundingSphere {
+        let center = AABB::from_points(pts).center();
+        let mut sqradius = 0.0f32;
+
+        for p in pts.iter() {
+            let sqdist = na::sqnorm(&(*p - center));
+
+            if sqdist > sqradius {
+                sqradius = sqdist
+            }
+        }
+
+        BoundingSphere::new(center, sqradius.sqrt())
+    }
+
+    /// The center of this bounding sphere.
+    pub fn center<'r>(&'r self) -> &'r Vec3<f32> {
+        &'r self.center
+    }
+
+    /// The radius of this bounding sphere.
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Computes the bounding sphere of this bounding sphere once scaled along each axis and then
+    /// transformed by an isometry.
+    pub fn transformed(&self, scale: &Vec3<f32>, transform: &Iso3<f32>) -> BoundingSphere {
+        let c = Vec3::new(self.center.x * scale.x, self.center.y * scale.y, self.center.z * scale.z);
+        let s = scale.x.abs().max(&scale.y.abs()).max(&scale.z.abs());
+
+        BoundingSphere::new(na::transform(transform, &c), self.radius * s)
+    }
+}
+
+fn inf(a: &Vec3<f32>, b: &Vec3<f32>) -> Vec3<f32> {
+    Vec3::new(a.x.min(&b.x), a.y.min(&b.y), a.z.min(&b.z))
+}
+
+fn sup(a: &Vec3<f32>, b: &Vec3<f32>) -> Vec3<f32> {
+    Vec3::new(a.x.max(&b.x), a.y.max(&b.y), a.z.max(&b.z))
+}
@@ -11,34 +11,34 @@ use builtins::cone_obj;
 use builtins::cylinder_obj;
 use builtins::capsule_obj;
 use resources::textures_manager;
+use resources::textures_manager::TextureParams;
 use mesh::Mesh;
 
 #[path = "../error.rs"]
 mod error;
 
 pub fn load(ctxt: &ObjectShaderContext) -> HashMap<~str, RcMut<Mesh>> {
+    // white texture used when an object has no diffuse texture
+    load_uniform_texture("default", 1.0, 1.0, 1.0);
+    // black texture used when an object has no specular or emissive texture
+    load_uniform_texture("default_black", 0.0, 0.0, 0.0);
+
+    verify!(gl::Uniform1i(ctxt.tex, 0));
+
+    parse_builtins()
+}
+
+fn load_uniform_texture(name: &str, r: GLfloat, g: GLfloat, b: GLfloat) {
     unsafe {
-        // create white texture
-        // Black/white checkerboard
-        let default_tex = textures_manager::singleton().add_empty("default");
-        let default_tex_pixels: [ GLfloat, ..3 ] = [
-            1.0, 1.0, 1.0
-            ];
+        let tex = textures_manager::singleton().add_empty(name);
+        let tex_pixels: [ GLfloat, ..3 ] = [ r, g, b ];
 
         verify!(gl::ActiveTexture(gl::TEXTURE0));
-        verify!(gl::BindTexture(gl::TEXTURE_2D, default_tex.borrow().id()));
-        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_BASE_LEVEL, 0));
-        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAX_LEVEL, 0));
-        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::REPEAT as i32));
-        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::REPEAT as i32));
-        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32));
-        verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR_MIPMAP_LINEAR as i32));
+        verify!(gl::BindTexture(gl::TEXTURE_2D, tex.borrow().id()));
         verify!(gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGB as i32, 1, 1, 0, gl::RGB, gl::FLOAT,
-                cast::transmute(&default_tex_pixels[0])));
-
-        verify!(gl::Uniform1i(ctxt.tex, 0));
+                cast::transmute(&tex_pixels[0])));
 
-        parse_builtins()
+        tex.borrow().set_params(&TextureParams::new());
     }
 }
 
@@ -0,0 +1,334 @@
+//! Smooth camera transitions and keyframed camera paths.
+
+use std::cmp;
+use nalgebra::na::Vec3;
+use extra::time;
+use camera::Camera;
+
+/// The way the progression of an animation is accelerated or decelerated.
+#[deriving(Eq, Clone, ToStr)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Starts slowly and accelerates.
+    EaseIn,
+    /// Starts quickly and decelerates.
+    EaseOut,
+    /// Starts slowly, accelerates, and decelerates at the end.
+    EaseInOut
+}
+
+impl Easing {
+    /// Maps a linear progression `t` in `[0, 1]` to the eased progression.
+    pub fn apply(&self, t: f32) -> f32 {
+        match *self {
+            Linear    => t,
+            EaseIn    => t * t * t,
+            EaseOut   => 1.0 - (1.0 - t) * (1.0 - t) * (1.0 - t),
+            EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                }
+                else {
+                    let u = 2.0 - 2.0 * t;
+
+                    1.0 - u * u * u / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// The position, focus point and field of view of a camera.
+#[deriving(Clone, ToStr)]
+pub struct CameraState {
+    /// The camera position.
+    eye: Vec3<f32>,
+    /// The point the camera is looking at.
+    at:  Vec3<f32>,
+    /// The vertical field of view, in radians. `None` leaves the camera field of view unchanged.
+    fov: Option<f32>
+}
+
+impl CameraState {
+    /// Creates a camera state which does not change the field of view.
+    pub fn new(eye: Vec3<f32>, at: Vec3<f32>) -> CameraState {
+        CameraState {
+            eye: eye,
+            at:  at,
+            fov: None
+        }
+    }
+
+    /// Creates a camera state with a field of view, in radians.
+    pub fn new_with_fov(eye: Vec3<f32>, at: Vec3<f32>, fov: f32) -> CameraState {
+        CameraState {
+            eye: eye,
+            at:  at,
+            fov: Some(fov)
+        }
+    }
+
+    /// The current state of a camera.
+    pub fn from_camera(camera: @mut Camera) -> CameraState {
+        CameraState {
+            eye: camera.eye(),
+            at:  camera.at(),
+            fov: camera.fov()
+        }
+    }
+
+    /// Moves a camera to this state.
+    pub fn apply(&self, camera: @mut Camera) {
+        camera.look_at(self.eye, self.at);
+
+        match self.fov {
+            Some(fov) => camera.set_fov(fov),
+            None      => { }
+        }
+    }
+}
+
+/// A camera path going through keyframes. The keyframe positions and focus points are
+/// interpolated by a Catmull-Rom spline, and the field of view linearly.
+///
+/// A path with two keyframes is a simple transition from one state to the other.
+#[deriving(Clone, ToStr)]
+pub struct CameraPath {
+    priv keyframes: ~[(f32, CameraState)], // sorted by time
+    priv easing:    Easing,
+    priv looping:   bool
+}
+
+impl CameraPath {
+    /// Creates an empty path, with linear easing and no loop.
+    pub fn new() -> CameraPath {
+        CameraPath {
+            keyframes: ~[],
+            easing:    Linear,
+            looping:   false
+        }
+    }
+
+    /// Creates a transition from one camera state to another.
+    ///
+    /// # Arguments
+    ///   * `from` - the state at the beginning of the transition
+    ///   * `to` - the state at the end of the transition
+    ///   * `duration` - the transition duration, in seconds
+    ///   * `easing` - the way the transition accelerates and decelerates
+    pub fn transition(from: CameraState, to: CameraState, duration: f32, easing: Easing) -> CameraPath {
+        let mut res = CameraPath::new();
+
+        res.add_keyframe(0.0, from);
+        res.add_keyframe(duration, to);
+        res.set_easing(easing);
+
+        res
+    }
+
+    /// Adds a keyframe. `time` is in seconds since the beginning of the path.
+    pub fn add_keyframe(&mut self, time: f32, state: CameraState) {
+        let i = match self.keyframes.iter().position(|&(t, _)| t > time) {
+            Some(i) => i,
+            None    => self.keyframes.len()
+        };
+
+        self.keyframes.insert(i, (time, state));
+    }
+
+    /// The number of keyframes of this path.
+    pub fn num_keyframes(&self) -> uint {
+        self.keyframes.len()
+    }
+
+    /// The time of the last keyframe, in seconds.
+    pub fn duration(&self) -> f32 {
+        match self.keyframes.last_opt() {
+            Some(&(t, _)) => t,
+            None          => 0.0
+        }
+    }
+
+    /// The easing applied to the whole path.
+    pub fn easing(&self) -> Easing {
+        self.easing
+    }
+
+    /// Sets the easing applied to the whole path. The default is `Linear`.
+    pub fn set_easing(&mut self, easing: Easing) {
+        self.easing = easing
+    }
+
+    /// Returns true if this path restarts from its beginning once its end is reached.
+    pub fn looping(&self) -> bool {
+        self.looping
+    }
+
+    /// Sets whether this path restarts from its beginning once its end is reached.
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping
+    }
+
+    /// The camera state at a given time, in seconds. The state of the first (resp. last) keyframe
+    /// is returned before (resp. after) the path. Returns `None` if the path has no keyframe.
+    pub fn state_at(&self, time: f32) -> Option<CameraState> {
+        let n = self.keyframes.len();
+
+        if n == 0 {
+            return None
+        }
+
+        let duration = self.duration();
+        let mut t    = time;
+
+        if duration > 0.0 {
+            if self.looping {
+                t = t % duration;
+            }
+
+            t = self.easing.apply(t.max(&0.0).min(&duration) / duration) * duration;
+        }
+
+        let (first_time, ref first) = self.keyframes[0];
+        let (last_time, ref last)   = self.keyframes[n - 1];
+
+        if t <= first_time {
+            return Some(first.clone())
+        }
+
+        if t >= last_time {
+            return Some(last.clone())
+        }
+
+        // `keyframes[i]` and `keyframes[i + 1]` surround `t`. The end keyframes are repeated to
+        // get the outer control points.
+        let i = self.keyframes.iter().rposition(|&(kt, _)| kt <= t).unwrap();
+        let (ta, ref a)     = self.keyframes[i];
+        let (tb, ref b)     = self.keyframes[i + 1];
+        let (_, ref before) = self.keyframes[if i == 0 { 0 } else { i - 1 }];
+        let (_, ref after)  = self.keyframes[cmp::min(i + 2, n - 1)];
+        let s = (t - ta) / (tb - ta);
+
+        let fov = match (a.fov, b.fov) {
+            (Some(fa), Some(fb)) => Some(fa + (fb - fa) * s),
+            _                    => b.fov
+        };
+
+        Some(CameraState {
+            eye: catmull_rom(&before.eye, &a.eye, &b.eye, &after.eye, s),
+            at:  catmull_rom(&before.at, &a.at, &b.at, &after.at, s),
+            fov: fov
+        })
+    }
+}
+
+/// A camera moving along a path. Once given to `Window::set_camera_animation`, the window moves
+/// the camera at the beginning of each frame.
+///
+/// By default, the animation follows the real time. A fixed time step can be set instead to get
+/// the same camera motion on every run independently of the framerate, e.g. to record videos.
+pub struct CameraAnimation {
+    priv camera:    @mut Camera,
+    priv path:      CameraPath,
+    priv time:      f32,
+    priv time_step: Option<f32>,
+    priv last_time: Option<u64>, // in nanoseconds
+    priv finished:  bool
+}
+
+impl CameraAnimation {
+    /// Creates an animation moving `camera` along `path`.
+    pub fn new(camera: @mut Camera, path: CameraPath) -> CameraAnimation {
+        CameraAnimation {
+            camera:    camera,
+            path:      path,
+            time:      0.0,
+            time_step: None,
+            last_time: None,
+            finished:  false
+        }
+    }
+
+    /// The animated camera.
+    pub fn camera(&self) -> @mut Camera {
+        self.camera
+    }
+
+    /// The path followed by the camera.
+    pub fn path<'r>(&'r self) -> &'r CameraPath {
+        &'r self.path
+    }
+
+    /// The time elapsed since the beginning of the animation, in seconds.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// Jumps to a given time of the animation, in seconds.
+    pub fn set_time(&mut self, time: f32) {
+        self.time     = time;
+        self.finished = false;
+    }
+
+    /// Sets the time, in seconds, the animation advances at each frame. `None` means the
+    /// animation follows the real time.
+    pub fn set_time_step(&mut self, time_step: Option<f32>) {
+        self.time_step = time_step
+    }
+
+    /// Returns true if the camera reached the end of a non-looping path.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Moves the camera to its state at the current time, and advances the time. This is called
+    /// by the window once per frame.
+    pub fn update(&mut self) {
+        match self.path.state_at(self.time) {
+            Some(state) => state.apply(self.camera),
+            None        => { }
+        }
+
+        let duration = self.path.duration();
+
+        if !self.path.looping() && self.time >= duration {
+            self.finished = true;
+
+            return
+        }
+
+        let dt = match self.time_step {
+            Some(dt) => dt,
+            None     => {
+                let now = time::precise_time_ns();
+                let dt  = match self.last_time {
+                    Some(last) => (now - last) as f32 / 1000000000.0,
+                    None       => 0.0
+                };
+
+                self.last_time = Some(now);
+
+                dt
+            }
+        };
+
+        self.time = self.time + dt;
+
+        // make sure the last state is exactly reached before the animation is finished
+        if !self.path.looping() && self.time > duration {
+            self.time = duration;
+        }
+    }
+}
+
+// Uniform Catmull-Rom interpolation between `p1` and `p2`.
+fn catmull_rom(p0: &Vec3<f32>, p1: &Vec3<f32>, p2: &Vec3<f32>, p3: &Vec3<f32>, t: f32) -> Vec3<f32> {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    (*p1 * 2.0 +
+     (*p2 - *p0) * t +
+     (*p0 * 2.0 - *p1 * 5.0 + *p2 * 4.0 - *p3) * t2 +
+     (*p1 * 3.0 - *p0 - *p2 * 3.0 + *p3) * t3) * 0.5
+}
@@ -3,15 +3,21 @@ use glfw;
 use nalgebra::na::{Vec2, Vec3, Mat4, Iso3};
 use nalgebra::na;
 use camera::Camera;
+use camera::CameraBindings;
+use camera::orthographic;
+use camera::camera;
+use bounding_volume::AABB;
 use event;
 
 /// Arc-ball camera mode. An arc-ball camera is a camera rotating around a fixed point (the focus
-/// point) and always looking at it. The following inputs are handled:
+/// point) and always looking at it. The following inputs are handled by default (see
+/// `set_bindings`):
 ///
 ///   * Left button press + drag - rotates the camera around the focus point
 ///   * Right button press + drag - translates the focus point on the plane orthogonal to the view
 ///   direction
-///   * Scroll in/out - zoom in/out
+///   * Scroll in/out - zoom in/out. In orthographic mode, the view extent changes instead of the
+///   distance to the focus point.
 ///   * Enter key - set the focus point to the origin
 #[deriving(Clone, ToStr)]
 pub struct ArcBall {
@@ -30,10 +36,18 @@ pub struct ArcBall {
     priv pitch_step: f32,
     /// Increment of the distance per unit scrolling. The default value is 40.0.
     priv dist_step:  f32,
+    /// The inputs controlling the camera.
+    priv bindings:   CameraBindings,
+
+    /// Whether the projection is orthographic instead of perspective.
+    priv ortho:      bool,
+    /// Height of the area visible in orthographic mode, in world units.
+    priv extent:     f32,
 
     priv fov:        f32,
     priv znear:      f32,
     priv zfar:       f32,
+    priv viewport:   Vec2<f32>,
     priv projection:      Mat4<f32>,
     priv proj_view:       Mat4<f32>,
     priv inv_proj_view:   Mat4<f32>,
@@ -60,9 +74,13 @@ impl ArcBall {
             yaw_step:   0.005,
             pitch_step: 0.005,
             dist_step:  40.0,
+            bindings:   CameraBindings::new(),
+            ortho:      false,
+            extent:     1.0,
             fov:        fov,
             znear:      znear,
             zfar:       zfar,
+            viewport:   Vec2::new(800.0, 600.0),
             projection: Mat4::new_perspective(800.0, 600.0, fov, znear, zfar),
             proj_view:  na::zero(),
             inv_proj_view:   na::zero(),
@@ -118,6 +136,76 @@ impl ArcBall {
         self.update_projviews();
     }
 
+    /// Increment of the yaw per unit mouse movement.
+    pub fn yaw_step(&self) -> f32 {
+        self.yaw_step
+    }
+
+    /// Sets the increment of the yaw per unit mouse movement. The default value is 0.005.
+    pub fn set_yaw_step(&mut self, step: f32) {
+        self.yaw_step = step
+    }
+
+    /// Increment of the pitch per unit mouse movement.
+    pub fn pitch_step(&self) -> f32 {
+        self.pitch_step
+    }
+
+    /// Sets the increment of the pitch per unit mouse movement. The default value is 0.005.
+    pub fn set_pitch_step(&mut self, step: f32) {
+        self.pitch_step = step
+    }
+
+    /// Increment of the distance per unit scrolling.
+    pub fn dist_step(&self) -> f32 {
+        self.dist_step
+    }
+
+    /// Sets the increment of the distance per unit scrolling. The default value is 40.0.
+    pub fn set_dist_step(&mut self, step: f32) {
+        self.dist_step = step
+    }
+
+    /// The inputs controlling this camera.
+    pub fn bindings<'r>(&'r self) -> &'r CameraBindings {
+        &'r self.bindings
+    }
+
+    /// Sets the inputs controlling this camera.
+    pub fn set_bindings(&mut self, bindings: CameraBindings) {
+        self.bindings = bindings
+    }
+
+    /// Returns true if this camera uses an orthographic projection.
+    pub fn is_orthographic(&self) -> bool {
+        self.ortho
+    }
+
+    /// Switches between the perspective and the orthographic projection. When switching to the
+    /// orthographic projection, the extent is set such that the focus point looks the same.
+    pub fn set_orthographic(&mut self, ortho: bool) {
+        if ortho && !self.ortho {
+            self.extent = 2.0 * self.dist * (self.fov / 2.0).tan();
+        }
+
+        self.ortho = ortho;
+        self.update_projection();
+    }
+
+    /// The height of the area visible in orthographic mode, in world units.
+    pub fn extent(&self) -> f32 {
+        self.extent
+    }
+
+    /// Sets the height of the area visible in orthographic mode, in world units. The visible width
+    /// is deduced from the viewport aspect ratio.
+    pub fn set_extent(&mut self, extent: f32) {
+        self.extent = extent;
+
+        self.update_restrictions();
+        self.update_projection();
+    }
+
     /// Move and orient the camera such that it looks at a specific point.
     pub fn look_at_z(&mut self, eye: Vec3<f32>, at: Vec3<f32>) {
         let dist  = na::norm(&(eye - at));
@@ -137,6 +225,10 @@ impl ArcBall {
             self.dist = 0.00001
         }
 
+        if (self.extent < 0.00001) {
+            self.extent = 0.00001
+        }
+
         if (self.pitch <= 0.0001) {
             self.pitch = 0.0001
         }
@@ -160,15 +252,36 @@ impl ArcBall {
         let dir       = na::normalize(&(self.at - eye));
         let tangent   = na::normalize(&na::cross(&Vec3::y(), &dir));
         let bitangent = na::cross(&dir, &tangent);
-        let mult      = self.dist / 1000.0;
+        let mult      = if self.ortho { self.extent / self.viewport.y } else { self.dist / 1000.0 };
 
         self.at = self.at + tangent * (dpos.x * mult) + bitangent * (dpos.y * mult);
         self.update_projviews();
     }
 
     fn handle_scroll(&mut self, off: f32) {
+        let dist = self.dist;
+
         self.dist = self.dist + self.dist_step * (off) / 120.0;
         self.update_restrictions();
+
+        if self.ortho {
+            // zoom as much as the perspective mode would, but without moving the camera
+            self.extent = self.extent * self.dist / dist;
+            self.dist   = dist;
+            self.update_restrictions();
+        }
+
+        self.update_projection();
+    }
+
+    fn update_projection(&mut self) {
+        self.projection = if self.ortho {
+            orthographic::projection(self.viewport.x, self.viewport.y, self.extent, self.znear, self.zfar)
+        }
+        else {
+            Mat4::new_perspective(self.viewport.x, self.viewport.y, self.fov, self.znear, self.zfar)
+        };
+
         self.update_projviews();
     }
 
@@ -183,6 +296,42 @@ impl Camera for ArcBall {
         (self.znear, self.zfar)
     }
 
+    fn at(&self) -> Vec3<f32> {
+        self.at
+    }
+
+    fn look_at(&mut self, eye: Vec3<f32>, at: Vec3<f32>) {
+        self.look_at_z(eye, at)
+    }
+
+    fn fov(&self) -> Option<f32> {
+        Some(self.fov)
+    }
+
+    fn set_fov(&mut self, fov: f32) {
+        self.fov = fov;
+        self.update_projection();
+    }
+
+    fn frame(&mut self, aabb: &AABB) {
+        let sphere = aabb.bounding_sphere();
+
+        self.at   = sphere.center().clone();
+        // in orthographic mode, this keeps the whole sphere in front of the camera
+        self.dist = camera::framing_distance(sphere.radius(), self.fov, self.viewport.x, self.viewport.y);
+
+        if self.ortho {
+            self.extent = camera::framing_extent(sphere.radius(), self.viewport.x, self.viewport.y);
+        }
+
+        let (znear, zfar) = camera::framing_clip_planes(self.dist, sphere.radius());
+        self.znear = znear;
+        self.zfar  = zfar;
+
+        self.update_restrictions();
+        self.update_projection();
+    }
+
     fn view_transform(&self) -> Iso3<f32> {
         let mut id: Iso3<f32> = na::one();
         id.look_at_z(&self.eye(), &self.at, &Vec3::y());
@@ -203,26 +352,26 @@ impl Camera for ArcBall {
             event::CursorPos(x, y) => {
                 let curr_pos = Vec2::new(x, y);
 
-                if window.get_mouse_button(glfw::MouseButtonLeft) == glfw::Press {
+                if self.bindings.rotating(window) {
                     let dpos = curr_pos - self.last_cursor_pos;
                     self.handle_left_button_displacement(&dpos)
                 }
 
-                if window.get_mouse_button(glfw::MouseButtonRight) == glfw::Press {
+                if self.bindings.panning(window) {
                     let dpos = curr_pos - self.last_cursor_pos;
                     self.handle_right_button_displacement(&dpos)
                 }
 
                 self.last_cursor_pos = curr_pos;
             },
-            event::KeyReleased(button) => if button == glfw::KeyEnter {
+            event::KeyReleased(button) => if self.bindings.is_reset(button) {
                 self.at = na::zero();
                 self.update_projviews();
             },
-            event::Scroll(_, off) => self.handle_scroll(off),
+            event::Scroll(_, off) => if self.bindings.scroll_zoom { self.handle_scroll(off) },
             event::FramebufferSize(w, h) => {
-                self.projection = Mat4::new_perspective(w, h, self.fov, self.znear, self.zfar);
-                self.update_projviews();
+                self.viewport = Vec2::new(w, h);
+                self.update_projection();
             },
             _ => { }
         }
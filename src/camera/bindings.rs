@@ -0,0 +1,132 @@
+//! Configurable mouse and keyboard inputs of the cameras.
+
+use glfw;
+
+/// No keyboard modifier.
+pub static NO_MODIFIER: u8 = 0;
+/// The shift keyboard modifier.
+pub static SHIFT: u8       = 1;
+/// The control keyboard modifier.
+pub static CONTROL: u8     = 2;
+/// The alt keyboard modifier.
+pub static ALT: u8         = 4;
+
+/// A mouse button held together with some keyboard modifiers.
+#[deriving(Clone, Eq, ToStr)]
+pub struct MouseBinding {
+    /// The mouse button.
+    button:    glfw::MouseButton,
+    /// The keyboard modifiers which must be held, e.g. `SHIFT | CONTROL`. The binding is not
+    /// active if any other modifier is held.
+    modifiers: u8
+}
+
+impl MouseBinding {
+    /// Creates a binding active when `button` is pressed with exactly the given `modifiers`.
+    pub fn new(button: glfw::MouseButton, modifiers: u8) -> MouseBinding {
+        MouseBinding {
+            button:    button,
+            modifiers: modifiers
+        }
+    }
+
+    /// Returns true if the button and the modifiers of this binding are currently held.
+    pub fn is_pressed(&self, window: &glfw::Window) -> bool {
+        window.get_mouse_button(self.button) == glfw::Press && held_modifiers(window) == self.modifiers
+    }
+}
+
+/// The inputs controlling a camera. An action is disabled by setting its binding to `None`, or
+/// its list of keys to an empty list. Cameras ignore the bindings of actions they do not support.
+#[deriving(Clone, ToStr)]
+pub struct CameraBindings {
+    /// Mouse drag rotating the camera: around its focus point for an arc-ball camera, around its
+    /// position for a first-person camera.
+    rotate:        Option<MouseBinding>,
+    /// Mouse drag translating the camera on the plane orthogonal to the view direction.
+    pan:           Option<MouseBinding>,
+    /// Whether scrolling zooms in and out.
+    scroll_zoom:   bool,
+    /// Key moving the focus point back to the origin.
+    reset:         Option<glfw::Key>,
+    /// Keys moving a first-person camera forward.
+    move_forward:  ~[glfw::Key],
+    /// Keys moving a first-person camera backward.
+    move_backward: ~[glfw::Key],
+    /// Keys moving a first-person camera to its left.
+    move_left:     ~[glfw::Key],
+    /// Keys moving a first-person camera to its right.
+    move_right:    ~[glfw::Key]
+}
+
+impl CameraBindings {
+    /// The default bindings: left button drag rotates, right button drag pans, scrolling zooms,
+    /// the enter key resets the focus point and the arrow keys move the camera.
+    pub fn new() -> CameraBindings {
+        CameraBindings {
+            rotate:        Some(MouseBinding::new(glfw::MouseButtonLeft, NO_MODIFIER)),
+            pan:           Some(MouseBinding::new(glfw::MouseButtonRight, NO_MODIFIER)),
+            scroll_zoom:   true,
+            reset:         Some(glfw::KeyEnter),
+            move_forward:  ~[ glfw::KeyUp ],
+            move_backward: ~[ glfw::KeyDown ],
+            move_left:     ~[ glfw::KeyLeft ],
+            move_right:    ~[ glfw::KeyRight ]
+        }
+    }
+
+    /// Bindings familiar to users of Blender and most CAD tools: middle button drag rotates,
+    /// shift + middle button drag pans, scrolling zooms, the home key resets the focus point, and
+    /// the WASD or arrow keys move the camera.
+    pub fn blender() -> CameraBindings {
+        CameraBindings {
+            rotate:        Some(MouseBinding::new(glfw::MouseButtonMiddle, NO_MODIFIER)),
+            pan:           Some(MouseBinding::new(glfw::MouseButtonMiddle, SHIFT)),
+            scroll_zoom:   true,
+            reset:         Some(glfw::KeyHome),
+            move_forward:  ~[ glfw::KeyW, glfw::KeyUp ],
+            move_backward: ~[ glfw::KeyS, glfw::KeyDown ],
+            move_left:     ~[ glfw::KeyA, glfw::KeyLeft ],
+            move_right:    ~[ glfw::KeyD, glfw::KeyRight ]
+        }
+    }
+
+    /// Returns true if the rotation binding is currently held.
+    pub fn rotating(&self, window: &glfw::Window) -> bool {
+        self.rotate.as_ref().map_default(false, |b| b.is_pressed(window))
+    }
+
+    /// Returns true if the translation binding is currently held.
+    pub fn panning(&self, window: &glfw::Window) -> bool {
+        self.pan.as_ref().map_default(false, |b| b.is_pressed(window))
+    }
+
+    /// Returns true if `key` is the key resetting the focus point.
+    pub fn is_reset(&self, key: glfw::Key) -> bool {
+        self.reset == Some(key)
+    }
+}
+
+/// Returns true if any of the keys is currently pressed.
+pub fn any_key_pressed(window: &glfw::Window, keys: &[glfw::Key]) -> bool {
+    keys.iter().any(|k| window.get_key(*k) == glfw::Press)
+}
+
+// The keyboard modifiers currently held.
+fn held_modifiers(window: &glfw::Window) -> u8 {
+    let mut res = NO_MODIFIER;
+
+    if any_key_pressed(window, [ glfw::KeyLeftShift, glfw::KeyRightShift ]) {
+        res = res | SHIFT;
+    }
+
+    if any_key_pressed(window, [ glfw::KeyLeftControl, glfw::KeyRightControl ]) {
+        res = res | CONTROL;
+    }
+
+    if any_key_pressed(window, [ glfw::KeyLeftAlt, glfw::KeyRightAlt ]) {
+        res = res | ALT;
+    }
+
+    res
+}
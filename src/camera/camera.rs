@@ -2,7 +2,9 @@ use std::cast;
 use glfw;
 use gl;
 use nalgebra::na::{Vec3, Mat4, Iso3};
+use nalgebra::na;
 use event;
+use bounding_volume::AABB;
 
 /// Trait every camera must implement.
 pub trait Camera {
@@ -27,6 +29,21 @@ pub trait Camera {
     fn inv_transformation(&self) -> Mat4<f32>;
     /// The clipping planes, aka. (`znear`, `zfar`).
     fn clip_planes(&self) -> (f32, f32); // FIXME: should this be here?
+    /// The point the camera is looking at. The default implementation returns the point at a unit
+    /// distance in front of the camera.
+    fn at(&self) -> Vec3<f32> {
+        self.eye() + na::rotate(&self.view_transform(), &Vec3::z())
+    }
+    /// Moves and orients the camera such that it looks at `at` from `eye`. This is used by the
+    /// camera animations. Cameras which cannot be moved ignore it.
+    fn look_at(&mut self, _eye: Vec3<f32>, _at: Vec3<f32>) { }
+    /// The vertical field of view, in radians. `None` for cameras without perspective.
+    fn fov(&self) -> Option<f32> { None }
+    /// Sets the vertical field of view, in radians. Cameras without perspective ignore it.
+    fn set_fov(&mut self, _fov: f32) { }
+    /// Moves the camera such that a bounding box fills the view, keeping the view direction. The
+    /// clip planes are moved around the box. Cameras which cannot be moved ignore it.
+    fn frame(&mut self, _aabb: &AABB) { }
 
     /*
      * Update & upload
@@ -57,3 +74,42 @@ pub trait Camera {
 
     fn render_complete(&self, _window: &glfw::Window) { }
 }
+
+/// The distance from which a sphere fills the view of a perspective camera.
+///
+/// # Arguments
+///   * `radius` - the sphere radius
+///   * `fov` - the vertical field of view, in radians
+///   * `width` - the viewport width
+///   * `height` - the viewport height
+#[doc(hidden)]
+pub fn framing_distance(radius: f32, fov: f32, width: f32, height: f32) -> f32 {
+    let half_vfov = fov / 2.0;
+    let half_hfov = (half_vfov.tan() * width / height).atan();
+
+    radius / half_vfov.min(&half_hfov).sin()
+}
+
+/// The clip planes `(znear, zfar)` such that a sphere at `dist` from the camera lies entirely
+/// between them. They only depend on the sphere, so framing a small object after a large one
+/// brings them closer again.
+#[doc(hidden)]
+pub fn framing_clip_planes(dist: f32, radius: f32) -> (f32, f32) {
+    // keep some margin on both sides to avoid clipping the sphere because of rounding errors
+    let near = ((dist - radius) * 0.5).max(&0.00001);
+    let far  = (dist + radius) * 2.0;
+
+    (near, far)
+}
+
+/// The extent (i-e. the visible height) such that a sphere fills the view of an orthographic
+/// camera.
+#[doc(hidden)]
+pub fn framing_extent(radius: f32, width: f32, height: f32) -> f32 {
+    if width < height {
+        2.0 * radius * height / width
+    }
+    else {
+        2.0 * radius
+    }
+}
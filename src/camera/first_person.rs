@@ -2,15 +2,19 @@ use std::num::atan2;
 use glfw;
 use nalgebra::na::{Vec2, Vec3, Mat4, Iso3};
 use nalgebra::na;
-use camera::Camera;
+use camera::{Camera, CameraBindings};
+use camera::bindings;
+use camera::camera;
+use bounding_volume::AABB;
 use event;
 
-/// First-person camera mode.
+/// First-person camera mode. The following inputs are handled by default (see `set_bindings`):
 ///
 ///   * Left button press + drag - look around
 ///   * Right button press + drag - translates the camera position on the plane orthogonal to the view
 ///   direction
 ///   * Scroll in/out - zoom in/out
+///   * Arrow keys - move the camera
 #[deriving(ToStr)]
 pub struct FirstPerson {
     /// The camera position
@@ -24,13 +28,16 @@ pub struct FirstPerson {
     priv yaw_step:   f32,
     /// Increment of the pitch per unit mouse movement. The default value is 0.005.
     priv pitch_step: f32,
-    /// Increment of the translation per arrow press. The default value is 0.1.
+    /// Increment of the translation per movement key press. The default value is 0.5.
     priv move_step:  f32,
+    /// The inputs controlling the camera.
+    priv bindings:   CameraBindings,
 
     /// Low level datas
     priv fov:        f32,
     priv znear:      f32,
     priv zfar:       f32,
+    priv viewport:   Vec2<f32>,
     priv projection:      Mat4<f32>,
     priv proj_view:       Mat4<f32>,
     priv inv_proj_view:   Mat4<f32>,
@@ -56,9 +63,11 @@ impl FirstPerson {
             yaw_step:      0.005,
             pitch_step:    0.005,
             move_step:     0.5,
+            bindings:      CameraBindings::new(),
             fov:        fov,
             znear:      znear,
             zfar:       zfar,
+            viewport:   Vec2::new(800.0, 600.0),
             projection: Mat4::new_perspective(800.0, 600.0, fov, znear, zfar),
             proj_view:  na::zero(),
             inv_proj_view:   na::zero(),
@@ -93,6 +102,47 @@ impl FirstPerson {
         Vec3::new(ax, ay, az)
     }
 
+    /// Increment of the yaw per unit mouse movement.
+    pub fn yaw_step(&self) -> f32 {
+        self.yaw_step
+    }
+
+    /// Sets the increment of the yaw per unit mouse movement. The default value is 0.005.
+    pub fn set_yaw_step(&mut self, step: f32) {
+        self.yaw_step = step
+    }
+
+    /// Increment of the pitch per unit mouse movement.
+    pub fn pitch_step(&self) -> f32 {
+        self.pitch_step
+    }
+
+    /// Sets the increment of the pitch per unit mouse movement. The default value is 0.005.
+    pub fn set_pitch_step(&mut self, step: f32) {
+        self.pitch_step = step
+    }
+
+    /// Increment of the translation per frame a movement key is held, or per unit scrolling.
+    pub fn move_step(&self) -> f32 {
+        self.move_step
+    }
+
+    /// Sets the increment of the translation per frame a movement key is held, or per unit
+    /// scrolling. The default value is 0.5.
+    pub fn set_move_step(&mut self, step: f32) {
+        self.move_step = step
+    }
+
+    /// The inputs controlling this camera.
+    pub fn bindings<'r>(&'r self) -> &'r CameraBindings {
+        &'r self.bindings
+    }
+
+    /// Sets the inputs controlling this camera.
+    pub fn set_bindings(&mut self, bindings: CameraBindings) {
+        self.bindings = bindings
+    }
+
     fn update_restrictions(&mut self) {
         if (self.pitch <= 0.0001) {
             self.pitch = 0.0001
@@ -146,6 +196,34 @@ impl Camera for FirstPerson {
         (self.znear, self.zfar)
     }
 
+    fn look_at(&mut self, eye: Vec3<f32>, at: Vec3<f32>) {
+        self.look_at_z(eye, at);
+    }
+
+    fn fov(&self) -> Option<f32> {
+        Some(self.fov)
+    }
+
+    fn set_fov(&mut self, fov: f32) {
+        self.fov = fov;
+        self.projection = Mat4::new_perspective(self.viewport.x, self.viewport.y, self.fov, self.znear, self.zfar);
+        self.update_projviews();
+    }
+
+    fn frame(&mut self, aabb: &AABB) {
+        let sphere = aabb.bounding_sphere();
+        let center = sphere.center().clone();
+        let dir    = na::normalize(&(self.at() - self.eye));
+        let dist   = camera::framing_distance(sphere.radius(), self.fov, self.viewport.x, self.viewport.y);
+
+        let (znear, zfar) = camera::framing_clip_planes(dist, sphere.radius());
+        self.znear      = znear;
+        self.zfar       = zfar;
+        self.projection = Mat4::new_perspective(self.viewport.x, self.viewport.y, self.fov, self.znear, self.zfar);
+
+        self.look_at_z(center - dir * dist, center);
+    }
+
     /// The camera view transformation (i-e transformation without projection).
     fn view_transform(&self) -> Iso3<f32> {
         let mut id: Iso3<f32> = na::one();
@@ -159,20 +237,21 @@ impl Camera for FirstPerson {
             event::CursorPos(x, y) => {
                 let curr_pos = Vec2::new(x, y);
 
-                if window.get_mouse_button(glfw::MouseButtonLeft) == glfw::Press {
+                if self.bindings.rotating(window) {
                     let dpos = curr_pos - self.last_cursor_pos;
                     self.handle_left_button_displacement(&dpos)
                 }
 
-                if window.get_mouse_button(glfw::MouseButtonRight) == glfw::Press {
+                if self.bindings.panning(window) {
                     let dpos = curr_pos - self.last_cursor_pos;
                     self.handle_right_button_displacement(&dpos)
                 }
 
                 self.last_cursor_pos = curr_pos;
             },
-            event::Scroll(_, off) => self.handle_scroll(off),
+            event::Scroll(_, off) => if self.bindings.scroll_zoom { self.handle_scroll(off) },
             event::FramebufferSize(w, h) => {
+                self.viewport   = Vec2::new(w, h);
                 self.projection = Mat4::new_perspective(w, h, self.fov, self.znear, self.zfar);
                 self.update_projviews();
             }
@@ -197,19 +276,19 @@ impl Camera for FirstPerson {
         let front: Vec3<f32> = na::rotate(&t, &Vec3::z());
         let right: Vec3<f32> = na::rotate(&t, &Vec3::x());
 
-        if window.get_key(glfw::KeyUp) == glfw::Press {
+        if bindings::any_key_pressed(window, self.bindings.move_forward) {
             self.eye = self.eye + front * self.move_step
         }
 
-        if window.get_key(glfw::KeyDown) == glfw::Press {
+        if bindings::any_key_pressed(window, self.bindings.move_backward) {
             self.eye = self.eye + front * (-self.move_step)
         }
 
-        if window.get_key(glfw::KeyRight) == glfw::Press {
+        if bindings::any_key_pressed(window, self.bindings.move_right) {
             self.eye = self.eye + right * (-self.move_step)
         }
 
-        if window.get_key(glfw::KeyLeft) == glfw::Press {
+        if bindings::any_key_pressed(window, self.bindings.move_left) {
             self.eye = self.eye + right * self.move_step
         }
 
@@ -4,6 +4,9 @@ pub use camera::camera::Camera;
 pub use camera::arc_ball::ArcBall;
 pub use camera::first_person::FirstPerson;
 pub use camera::first_person_stereo::FirstPersonStereo;
+pub use camera::orthographic::Orthographic;
+pub use camera::bindings::{CameraBindings, MouseBinding};
+pub use camera::animation::{CameraAnimation, CameraPath, CameraState};
 
 #[doc(hidden)]
 pub mod camera;
@@ -13,3 +16,7 @@ pub mod arc_ball;
 pub mod first_person;
 #[doc(hidden)]
 pub mod first_person_stereo;
+#[doc(hidden)]
+pub mod orthographic;
+pub mod bindings;
+pub mod animation;
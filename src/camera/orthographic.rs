@@ -0,0 +1,277 @@
+use glfw;
+use nalgebra::na::{Vec2, Vec3, Mat4, Iso3};
+use nalgebra::na;
+use camera::{Camera, CameraBindings};
+use camera::camera;
+use bounding_volume::AABB;
+use event;
+
+/// Orthographic camera mode. An orthographic camera looks at a focus point along a fixed
+/// direction, without perspective distortion. This is the camera of technical drawings, e.g. the
+/// top, front and side views of a CAD quad view. The following inputs are handled by default
+/// (see `set_bindings`):
+///
+///   * Left or right button press + drag - translates the focus point on the plane orthogonal to
+///   the view direction. Both the rotation and the translation bindings translate since this
+///   camera cannot rotate.
+///   * Scroll in/out - zoom in/out, i-e. changes the view extent
+///   * Enter key - set the focus point to the origin
+#[deriving(Clone, ToStr)]
+pub struct Orthographic {
+    /// The focus point.
+    priv at:     Vec3<f32>,
+    /// The normalized view direction.
+    priv dir:    Vec3<f32>,
+    /// Distance from the camera to the `at` focus point. It does not change the apparent size of
+    /// the objects: it only moves the clipping planes.
+    priv dist:   f32,
+    /// Height of the scene area visible by the camera, in world units.
+    priv extent: f32,
+
+    /// Zoom factor per unit scrolling: the extent is multiplied by `exp(extent_step * scroll)`.
+    /// The default value is 0.1.
+    priv extent_step: f32,
+    /// The inputs controlling the camera.
+    priv bindings:    CameraBindings,
+
+    priv znear:           f32,
+    priv zfar:            f32,
+    priv viewport:        Vec2<f32>,
+    priv projection:      Mat4<f32>,
+    priv proj_view:       Mat4<f32>,
+    priv inv_proj_view:   Mat4<f32>,
+    priv last_cursor_pos: Vec2<f32>
+}
+
+impl Orthographic {
+    /// Creates a new orthographic camera showing `extent` world units vertically.
+    pub fn new(eye: Vec3<f32>, at: Vec3<f32>, extent: f32) -> Orthographic {
+        Orthographic::new_with_clip_planes(0.1, 1024.0, eye, at, extent)
+    }
+
+    /// Creates a new orthographic camera with default sensitivity values.
+    pub fn new_with_clip_planes(znear:  f32,
+                                zfar:   f32,
+                                eye:    Vec3<f32>,
+                                at:     Vec3<f32>,
+                                extent: f32) -> Orthographic {
+        let mut res = Orthographic {
+            at:          na::zero(),
+            dir:         -Vec3::z(),
+            dist:        1.0,
+            extent:      extent,
+            extent_step: 0.1,
+            bindings:    CameraBindings::new(),
+            znear:       znear,
+            zfar:        zfar,
+            viewport:    Vec2::new(800.0, 600.0),
+            projection:  projection(800.0, 600.0, extent, znear, zfar),
+            proj_view:   na::zero(),
+            inv_proj_view:   na::zero(),
+            last_cursor_pos: na::zero()
+        };
+
+        res.look_at_z(eye, at);
+
+        res
+    }
+
+    /// The point the camera is looking at.
+    pub fn at(&self) -> Vec3<f32> {
+        self.at
+    }
+
+    /// Moves the camera such that it looks at `at`, keeping its view direction.
+    pub fn set_at(&mut self, at: Vec3<f32>) {
+        self.at = at;
+
+        self.update_projviews();
+    }
+
+    /// The height of the scene area visible by the camera, in world units.
+    pub fn extent(&self) -> f32 {
+        self.extent
+    }
+
+    /// Sets the height of the scene area visible by the camera, in world units. The visible
+    /// width is deduced from the viewport aspect ratio.
+    pub fn set_extent(&mut self, extent: f32) {
+        self.extent = extent;
+
+        self.update_restrictions();
+        self.update_projection();
+    }
+
+    /// Zoom factor per unit scrolling.
+    pub fn extent_step(&self) -> f32 {
+        self.extent_step
+    }
+
+    /// Sets the zoom factor per unit scrolling: the extent is multiplied by
+    /// `exp(extent_step * scroll)`. The default value is 0.1.
+    pub fn set_extent_step(&mut self, step: f32) {
+        self.extent_step = step
+    }
+
+    /// The inputs controlling this camera.
+    pub fn bindings<'r>(&'r self) -> &'r CameraBindings {
+        &'r self.bindings
+    }
+
+    /// Sets the inputs controlling this camera.
+    pub fn set_bindings(&mut self, bindings: CameraBindings) {
+        self.bindings = bindings
+    }
+
+    /// Move and orient the camera such that it looks at a specific point.
+    pub fn look_at_z(&mut self, eye: Vec3<f32>, at: Vec3<f32>) {
+        let dist = na::norm(&(at - eye));
+
+        self.at   = at;
+        self.dir  = (at - eye) / dist;
+        self.dist = dist;
+        self.update_projviews();
+    }
+
+    // The up direction is `y` unless the camera looks along it. In this case `-z` is up when
+    // looking down, so that a top view is seen like a map.
+    fn up(&self) -> Vec3<f32> {
+        if self.dir.y.abs() > 0.9999 {
+            if self.dir.y < 0.0 { -Vec3::z() } else { Vec3::z() }
+        }
+        else {
+            Vec3::y()
+        }
+    }
+
+    fn update_restrictions(&mut self) {
+        if (self.extent < 0.00001) {
+            self.extent = 0.00001
+        }
+    }
+
+    fn handle_button_displacement(&mut self, dpos: &Vec2<f32>) {
+        let tangent   = na::normalize(&na::cross(&self.up(), &self.dir));
+        let bitangent = na::cross(&self.dir, &tangent);
+        // the focus point follows the cursor exactly
+        let mult      = self.extent / self.viewport.y;
+
+        self.at = self.at + tangent * (dpos.x * mult) + bitangent * (dpos.y * mult);
+        self.update_projviews();
+    }
+
+    fn handle_scroll(&mut self, off: f32) {
+        self.extent = self.extent * (self.extent_step * off).exp();
+        self.update_restrictions();
+        self.update_projection();
+    }
+
+    fn update_projection(&mut self) {
+        self.projection = projection(self.viewport.x, self.viewport.y, self.extent, self.znear, self.zfar);
+        self.update_projviews();
+    }
+
+    fn update_projviews(&mut self) {
+        self.proj_view     = self.projection * na::to_homogeneous(&na::inv(&self.view_transform()).unwrap());
+        self.inv_proj_view = na::inv(&self.proj_view).unwrap();
+    }
+}
+
+impl Camera for Orthographic {
+    fn clip_planes(&self) -> (f32, f32) {
+        (self.znear, self.zfar)
+    }
+
+    fn at(&self) -> Vec3<f32> {
+        self.at
+    }
+
+    fn look_at(&mut self, eye: Vec3<f32>, at: Vec3<f32>) {
+        self.look_at_z(eye, at)
+    }
+
+    fn frame(&mut self, aabb: &AABB) {
+        let sphere = aabb.bounding_sphere();
+
+        self.at     = sphere.center().clone();
+        self.extent = camera::framing_extent(sphere.radius(), self.viewport.x, self.viewport.y);
+        // keep the whole sphere in front of the camera
+        self.dist   = sphere.radius() * 2.0;
+
+        let (znear, zfar) = camera::framing_clip_planes(self.dist, sphere.radius());
+        self.znear = znear;
+        self.zfar  = zfar;
+
+        self.update_restrictions();
+        self.update_projection();
+    }
+
+    fn view_transform(&self) -> Iso3<f32> {
+        let mut id: Iso3<f32> = na::one();
+        id.look_at_z(&self.eye(), &self.at, &self.up());
+
+        id
+    }
+
+    fn eye(&self) -> Vec3<f32> {
+        self.at - self.dir * self.dist
+    }
+
+    fn handle_event(&mut self, window: &glfw::Window, event: &event::Event) {
+        match *event {
+            event::CursorPos(x, y) => {
+                let curr_pos = Vec2::new(x, y);
+
+                if self.bindings.rotating(window) || self.bindings.panning(window) {
+                    let dpos = curr_pos - self.last_cursor_pos;
+                    self.handle_button_displacement(&dpos)
+                }
+
+                self.last_cursor_pos = curr_pos;
+            },
+            event::KeyReleased(button) => if self.bindings.is_reset(button) {
+                self.at = na::zero();
+                self.update_projviews();
+            },
+            event::Scroll(_, off) => if self.bindings.scroll_zoom { self.handle_scroll(off) },
+            event::FramebufferSize(w, h) => {
+                self.viewport = Vec2::new(w, h);
+                self.update_projection();
+            },
+            _ => { }
+        }
+    }
+
+    fn transformation(&self) -> Mat4<f32> {
+        self.proj_view
+    }
+
+    fn inv_transformation(&self) -> Mat4<f32> {
+        self.inv_proj_view
+    }
+
+    fn update(&mut self, _: &glfw::Window) { }
+}
+
+/// Builds an orthographic projection matrix. The view space looks down the positive `z` axis
+/// with `y` up, so `x` is flipped to keep the right-hand side of the view on the right of the
+/// screen.
+///
+/// # Arguments
+///   * `width` - the viewport width, in pixels
+///   * `height` - the viewport height, in pixels
+///   * `extent` - the height of the visible area, in world units. Its width is deduced from the
+///   viewport aspect ratio.
+///   * `znear` - the distance to the near clipping plane
+///   * `zfar` - the distance to the far clipping plane
+pub fn projection(width: f32, height: f32, extent: f32, znear: f32, zfar: f32) -> Mat4<f32> {
+    let sy = 2.0 / extent;
+    let sx = -sy * height / width;
+    let sz = 2.0 / (zfar - znear);
+    let tz = -(zfar + znear) / (zfar - znear);
+
+    Mat4::new(sx,  0.0, 0.0, 0.0,
+              0.0, sy,  0.0, 0.0,
+              0.0, 0.0, sz,  tz,
+              0.0, 0.0, 0.0, 1.0)
+}
@@ -0,0 +1,86 @@
+//! Polling-based watcher of file modifications.
+
+use std::rt::io;
+use std::rt::io::file::FileInfo;
+use std::hashmap::HashMap;
+use extra::time;
+
+/// A set of files checked periodically for modifications. The files are polled: no platform
+/// specific notification mechanism is used.
+pub struct FileWatcher {
+    priv files:     HashMap<~str, Option<u64>>, // last known modification time of each file
+    priv period:    u64,                        // in nanoseconds
+    priv last_poll: u64
+}
+
+impl FileWatcher {
+    /// Creates a watcher checking its files at most once every `period_ms` milliseconds.
+    pub fn new(period_ms: u64) -> FileWatcher {
+        FileWatcher {
+            files:     HashMap::new(),
+            period:    period_ms * 1000000,
+            last_poll: 0
+        }
+    }
+
+    /// Starts watching a file. Nothing happens if the file is already watched. A file which does
+    /// not exist is reported as modified once it is created.
+    pub fn watch(&mut self, path: &str) {
+        let key = path.to_owned();
+
+        if !self.files.contains_key(&key) {
+            self.files.insert(key, modification_time(path));
+        }
+    }
+
+    /// Stops watching a file.
+    pub fn unwatch(&mut self, path: &str) {
+        self.files.remove(&path.to_owned());
+    }
+
+    /// Returns true if `path` is watched.
+    pub fn is_watched(&self, path: &str) -> bool {
+        self.files.contains_key(&path.to_owned())
+    }
+
+    /// Returns true if the watcher period elapsed since the last check, i-e. if `poll` will
+    /// actually check the files.
+    pub fn due(&self) -> bool {
+        time::precise_time_ns() - self.last_poll >= self.period
+    }
+
+    /// Returns the files modified since the last time they were reported. Files are not checked
+    /// if the last check is more recent than the watcher period: an empty list is returned.
+    pub fn poll(&mut self) -> ~[~str] {
+        if !self.due() {
+            return ~[]
+        }
+
+        self.last_poll = time::precise_time_ns();
+
+        let mut modified = ~[];
+
+        for (path, last_time) in self.files.mut_iter() {
+            let mtime = modification_time(*path);
+
+            // a file being written might not be readable: wait until it exists again.
+            if mtime.is_some() && mtime != *last_time {
+                *last_time = mtime;
+                modified.push(path.clone());
+            }
+        }
+
+        modified
+    }
+}
+
+fn modification_time(path: &str) -> Option<u64> {
+    let mut res = None;
+
+    // a missing file raises an io error
+    do io::io_error::cond.trap(|_| { }).inside {
+        res = Path::new(path).stat().map(|s| s.modified)
+    }
+
+    res
+}
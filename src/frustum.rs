@@ -0,0 +1,85 @@
+//! View frustum, used to cull the objects which are not visible by the camera.
+
+use nalgebra::na::{Vec3, Vec4, Mat4};
+use nalgebra::na;
+use bounding_volume::{AABB, BoundingSphere};
+
+/// The view frustum of a camera, described by six planes: left, right, bottom, top, near, far.
+/// Each plane is given as a vector `(a, b, c, d)` such that the points `p` inside of the frustum
+/// satisfy `a * p.x + b * p.y + c * p.z + d >= 0`.
+#[deriving(Clone, ToStr)]
+pub struct Frustum {
+    priv planes: [Vec4<f32>, ..6]
+}
+
+impl Frustum {
+    /// Extracts the frustum planes from a camera transformation (i-e. the projection matrix
+    /// multiplied by the view matrix). See `Camera::transformation`.
+    pub fn from_transformation(transformation: &Mat4<f32>) -> Frustum {
+        // A point `p` is inside of the frustum if its clip-space coordinates `c = M * p` satisfy
+        // `-c.w <= c.x <= c.w` (and the same for `y` and `z`). Each inequality, e.g.
+        // `c.x + c.w >= 0`, is a plane equation given by the transpose of `M` multiplied by the
+        // corresponding vector of coefficients, e.g. `(1, 0, 0, 1)`.
+        let t = na::transpose(transformation);
+
+        let mut res = Frustum {
+            planes: [
+                t * Vec4::new(1.0, 0.0, 0.0, 1.0),
+                t * Vec4::new(-1.0, 0.0, 0.0, 1.0),
+                t * Vec4::new(0.0, 1.0, 0.0, 1.0),
+                t * Vec4::new(0.0, -1.0, 0.0, 1.0),
+                t * Vec4::new(0.0, 0.0, 1.0, 1.0),
+                t * Vec4::new(0.0, 0.0, -1.0, 1.0)
+            ]
+        };
+
+        // normalize the planes so that the plane equation gives an euclidean distance
+        for p in res.planes.mut_iter() {
+            let n = na::norm(&Vec3::new(p.x, p.y, p.z));
+
+            if n != 0.0 {
+                *p = *p / n;
+            }
+        }
+
+        res
+    }
+
+    /// The six planes of this frustum: left, right, bottom, top, near, far.
+    pub fn planes<'r>(&'r self) -> &'r [Vec4<f32>] {
+        let res: &'r [Vec4<f32>] = self.planes;
+
+        res
+    }
+
+    /// Tests if a point is inside of this frustum.
+    pub fn contains_point(&self, p: &Vec3<f32>) -> bool {
+        self.planes.iter().all(|pl| signed_dist(pl, p) >= 0.0)
+    }
+
+    /// Conservatively tests if a sphere intersects this frustum. Some spheres outside of the
+    /// frustum might be reported as intersecting, but no intersecting sphere is reported as
+    /// outside.
+    pub fn intersects_sphere(&self, sphere: &BoundingSphere) -> bool {
+        self.planes.iter().all(|pl| signed_dist(pl, sphere.center()) >= -sphere.radius())
+    }
+
+    /// Conservatively tests if an axis-aligned box intersects this frustum. Some boxes outside of
+    /// the frustum might be reported as intersecting, but no intersecting box is reported as
+    /// outside.
+    pub fn intersects_aabb(&self, aabb: &AABB) -> bool {
+        do self.planes.iter().all |pl| {
+            // the corner of the box which is the farthest along the plane normal
+            let farthest = Vec3::new(
+                if pl.x >= 0.0 { aabb.maxs().x } else { aabb.mins().x },
+                if pl.y >= 0.0 { aabb.maxs().y } else { aabb.mins().y },
+                if pl.z >= 0.0 { aabb.maxs().z } else { aabb.mins().z });
+
+            signed_dist(pl, &farthest) >= 0.0
+        }
+    }
+}
+
+fn signed_dist(plane: &Vec4<f32>, p: &Vec3<f32>) -> f32 {
+    plane.x * p.x + plane.y * p.y + plane.z * p.z + plane.w
+}
@@ -0,0 +1,40 @@
+//! Queries of the capabilities of the current opengl context.
+
+use std::str;
+use std::cast;
+use gl;
+use gl::types::*;
+
+/// Returns true if the version of the current opengl context is at least `major.minor`.
+pub fn gl_version_at_least(major: uint, minor: uint) -> bool {
+    // the version string starts with `<major>.<minor>`
+    let version     = gl_string(gl::VERSION);
+    let mut numbers = version.split_iter(|c: char| !c.is_digit()).filter_map(|n| from_str::<uint>(n));
+
+    match (numbers.next(), numbers.next()) {
+        (Some(ma), Some(mi)) => ma > major || (ma == major && mi >= minor),
+        _                    => false
+    }
+}
+
+/// Returns true if the current opengl context advertises an extension. Only meaningful for
+/// contexts older than 3.0: recent core contexts do not list their extensions this way.
+pub fn gl_has_extension(name: &str) -> bool {
+    gl_string(gl::EXTENSIONS).split_iter(' ').any(|e| e == name)
+}
+
+fn gl_string(name: GLenum) -> ~str {
+    unsafe {
+        let s = gl::GetString(name);
+
+        if s.is_null() {
+            // clear the error raised by an unsupported query
+            gl::GetError();
+
+            ~""
+        }
+        else {
+            str::raw::from_c_str(cast::transmute(s))
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! Data structure of a set of objects sharing the same mesh, drawn with a single draw call.
+
+use std::ptr;
+use std::cast;
+use std::mem;
+use std::vec;
+use std::util;
+use std::borrow;
+use std::rc::{RcMut, Rc};
+use gl;
+use gl::types::*;
+use nalgebra::na::{Mat3, Mat4, Vec3, Iso3};
+use nalgebra::na;
+use resources::shaders_manager::InstancedObjectShaderContext;
+use resources::textures_manager;
+use resources::textures_manager::{Texture, TextureParams};
+use mesh::Mesh;
+use gl_info::{gl_version_at_least, gl_has_extension};
+
+#[path = "error.rs"]
+mod error;
+
+/// Number of floats per instance: the transformation matrix (16) and the color (3).
+static INSTANCE_SIZE: uint = 19;
+
+/// Returns true if the current opengl context supports instanced rendering, i-e. if it is at
+/// least an opengl 3.3 context or if it has the `GL_ARB_instanced_arrays` and
+/// `GL_ARB_draw_instanced` extensions.
+pub fn is_supported() -> bool {
+    gl_version_at_least(3, 3) ||
+    (gl_has_extension("GL_ARB_instanced_arrays") && gl_has_extension("GL_ARB_draw_instanced"))
+}
+
+/// Set of datas identifying a set of instances.
+pub struct InstancedObjectData {
+    priv texture:    Rc<Texture>,
+    priv scale:      Mat3<GLfloat>,
+    priv transforms: ~[Iso3<f32>],
+    priv colors:     ~[Vec3<f32>],
+    priv visible:    bool,
+    priv ibuf:       GLuint,
+    priv capacity:   uint, // in number of instances
+    priv dirty:      bool
+}
+
+/// Structure of a set of copies of the same mesh. Each copy (instance) has its own
+/// transformation and color, but all share the same scale and texture. All the instances are drawn
+/// with a single draw call, making this much faster than an `Object` per copy.
+#[deriving(Clone)]
+pub struct InstancedObject {
+    priv data: RcMut<InstancedObjectData>,
+    priv mesh: RcMut<Mesh>
+}
+
+impl InstancedObject {
+    #[doc(hidden)]
+    pub fn new(mesh:       RcMut<Mesh>,
+               transforms: ~[Iso3<f32>],
+               colors:     ~[Vec3<f32>],
+               texture:    Rc<Texture>,
+               sx:         GLfloat,
+               sy:         GLfloat,
+               sz:         GLfloat) -> InstancedObject {
+        assert!(transforms.len() == colors.len(), "There must be exactly one color per instance.");
+
+        let mut ibuf: GLuint = 0;
+
+        unsafe { verify!(gl::GenBuffers(1, &mut ibuf)) };
+
+        let data = InstancedObjectData {
+            texture:    texture,
+            scale:      Mat3::new(sx, 0.0, 0.0,
+                                  0.0, sy, 0.0,
+                                  0.0, 0.0, sz),
+            transforms: transforms,
+            colors:     colors,
+            visible:    true,
+            ibuf:       ibuf,
+            capacity:   0,
+            dirty:      true
+        };
+
+        InstancedObject {
+            data: RcMut::new(data),
+            mesh: mesh
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn upload(&self, context: &InstancedObjectShaderContext) {
+        do self.data.with_mut_borrow |data| {
+            if data.visible && data.transforms.len() != 0 {
+                data.update_buffer();
+
+                let stride = (INSTANCE_SIZE * mem::size_of::<GLfloat>()) as GLint;
+
+                unsafe {
+                    verify!(gl::UniformMatrix3fv(context.scale, 1, gl::FALSE as u8, cast::transmute(&data.scale)));
+
+                    self.mesh.with_borrow(|m| m.bind(context.pos, context.normal, context.tex_coord));
+
+                    // per-instance attributes: a mat4 attribute uses four consecutive locations
+                    verify!(gl::BindBuffer(gl::ARRAY_BUFFER, data.ibuf));
+
+                    for i in range(0u, 4) {
+                        let loc = context.inst_transform + i as GLuint;
+
+                        verify!(gl::VertexAttribPointer(loc, 4, gl::FLOAT, gl::FALSE as u8, stride,
+                                                        cast::transmute(i * 4 * mem::size_of::<GLfloat>())));
+                        verify!(gl::VertexAttribDivisor(loc, 1));
+                    }
+
+                    verify!(gl::VertexAttribPointer(context.inst_color, 3, gl::FLOAT, gl::FALSE as u8, stride,
+                                                    cast::transmute(16 * mem::size_of::<GLfloat>())));
+                    verify!(gl::VertexAttribDivisor(context.inst_color, 1));
+
+                    verify!(gl::ActiveTexture(gl::TEXTURE0));
+                    verify!(gl::BindTexture(gl::TEXTURE_2D, data.texture.borrow().id()));
+
+                    verify!(gl::DrawElementsInstanced(gl::TRIANGLES,
+                                                      self.mesh.with_borrow(|m| m.num_pts()) as GLint,
+                                                      gl::UNSIGNED_INT,
+                                                      ptr::null(),
+                                                      data.transforms.len() as GLint));
+
+                    // the divisors are a global state: reset them so that they do not affect
+                    // other shaders using the same attribute locations.
+                    for i in range(0u, 4) {
+                        verify!(gl::VertexAttribDivisor(context.inst_transform + i as GLuint, 0));
+                    }
+                    verify!(gl::VertexAttribDivisor(context.inst_color, 0));
+
+                    self.mesh.with_borrow(|m| m.unbind());
+                }
+            }
+        }
+    }
+
+    /// Sets the visible state of all the instances. An invisible object does not draw itself.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.data.with_mut_borrow(|d| d.visible = visible)
+    }
+
+    /// Returns true if the instances can be visible.
+    pub fn visible(&self) -> bool {
+        self.data.with_borrow(|d| d.visible)
+    }
+
+    /// The number of instances.
+    pub fn num_instances(&self) -> uint {
+        self.data.with_borrow(|d| d.transforms.len())
+    }
+
+    /// Sets the local scaling factor shared by all the instances.
+    pub fn set_scale(&mut self, sx: f32, sy: f32, sz: f32) {
+        do self.data.with_mut_borrow |d| {
+            d.scale = Mat3::new(
+                sx, 0.0, 0.0,
+                0.0, sy, 0.0,
+                0.0, 0.0, sz)
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn texture(&self) -> Rc<Texture> {
+        self.data.with_borrow(|d| d.texture.clone())
+    }
+
+    /// Sets the texture shared by all the instances.
+    ///
+    /// # Arguments
+    ///   * `path` - relative path of the texture on the disk
+    pub fn set_texture(&mut self, path: &str) {
+        let texture = textures_manager::singleton().add(path, &TextureParams::new());
+
+        self.data.with_mut_borrow(|d| d.texture = texture.clone());
+    }
+
+    /// Adds a new instance.
+    pub fn push_instance(&mut self, transform: Iso3<f32>, color: Vec3<f32>) {
+        do self.data.with_mut_borrow |d| {
+            d.transforms.push(transform.clone());
+            d.colors.push(color);
+            d.dirty = true;
+        }
+    }
+
+    /// Sets the transformation of the `i`-th instance.
+    pub fn set_instance_transformation(&mut self, i: uint, transform: Iso3<f32>) {
+        do self.data.with_mut_borrow |d| {
+            d.transforms[i] = transform.clone();
+            d.dirty         = true;
+        }
+    }
+
+    /// Sets the color of the `i`-th instance. Colors components must be on the range `[0.0, 1.0]`.
+    pub fn set_instance_color(&mut self, i: uint, r: f32, g: f32, b: f32) {
+        do self.data.with_mut_borrow |d| {
+            d.colors[i] = Vec3::new(r, g, b);
+            d.dirty     = true;
+        }
+    }
+
+    /// Replaces all the instances. There must be exactly one color per transformation.
+    pub fn set_instances(&mut self, transforms: ~[Iso3<f32>], colors: ~[Vec3<f32>]) {
+        assert!(transforms.len() == colors.len(), "There must be exactly one color per instance.");
+
+        let mut transforms = transforms;
+        let mut colors     = colors;
+
+        do self.data.with_mut_borrow |d| {
+            util::swap(&mut d.transforms, &mut transforms);
+            util::swap(&mut d.colors, &mut colors);
+            d.dirty = true;
+        }
+    }
+
+    /// Get a write access to the instances transformations and colors. They will be re-uploaded
+    /// to the gpu during the next frame.
+    pub fn modify_instances(&mut self, f: &fn(&mut [Iso3<f32>], &mut [Vec3<f32>])) {
+        do self.data.with_mut_borrow |d| {
+            f(d.transforms, d.colors);
+            d.dirty = true;
+        }
+    }
+}
+
+impl InstancedObjectData {
+    fn update_buffer(&mut self) {
+        if !self.dirty {
+            return
+        }
+
+        let num_instances = self.transforms.len();
+        let mut buf       = vec::with_capacity(num_instances * INSTANCE_SIZE);
+
+        for (t, c) in self.transforms.iter().zip(self.colors.iter()) {
+            let formated_transform: Mat4<f32>     = na::to_homogeneous(t);
+            let components:         [GLfloat, ..16] = unsafe { cast::transmute(formated_transform) };
+
+            buf.push_all(components);
+            buf.push(c.x);
+            buf.push(c.y);
+            buf.push(c.z);
+        }
+
+        verify!(gl::BindBuffer(gl::ARRAY_BUFFER, self.ibuf));
+
+        unsafe {
+            if num_instances > self.capacity {
+                // realloc the instances buffer
+                self.capacity = num_instances;
+
+                verify!(gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    (buf.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                    cast::transmute(&buf[0]),
+                    gl::DYNAMIC_DRAW));
+            }
+            else {
+                verify!(gl::BufferSubData(
+                    gl::ARRAY_BUFFER,
+                    0,
+                    (buf.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                    cast::transmute(&buf[0])));
+            }
+        }
+
+        self.dirty = false;
+    }
+}
+
+impl Eq for InstancedObject {
+    fn eq(&self, other: &InstancedObject) -> bool {
+        self.data.with_borrow(|d1| other.data.with_borrow(|d2| borrow::ref_eq(d1, d2)))
+    }
+}
+
+impl Drop for InstancedObjectData {
+    fn drop(&mut self) {
+        unsafe { verify!(gl::DeleteBuffers(1, &self.ibuf)); }
+    }
+}
@@ -21,9 +21,17 @@ extern mod stb_image;
 pub mod window;
 pub mod event;
 pub mod object;
+pub mod instanced_object;
+pub mod polyline;
 pub mod obj;
 pub mod mesh;
+pub mod mesh_ops;
+pub mod bounding_volume;
+pub mod frustum;
+pub mod background;
 pub mod camera;
+pub mod viewport;
+pub mod file_watcher;
 
 /*
  * the user should not see/use the following modules
@@ -34,6 +42,18 @@ pub mod shaders;
 #[doc(hidden)]
 pub mod lines_manager;
 
+#[doc(hidden)]
+pub mod overlay_manager;
+
+#[doc(hidden)]
+pub mod text_renderer;
+
+#[doc(hidden)]
+pub mod gl_info;
+
+#[doc(hidden)]
+pub mod skybox;
+
 #[doc(hidden)]
 pub mod builtins
 {
@@ -43,6 +63,7 @@ pub mod builtins
     pub mod cone_obj;
     pub mod cylinder_obj;
     pub mod capsule_obj;
+    pub mod font8x8;
 }
 
 /// A bunch of post-processing effects.
@@ -4,16 +4,19 @@ use std::mem;
 use gl;
 use gl::types::*;
 use nalgebra::na::Vec3;
-use resources::shaders_manager::LinesShaderContext;
+use resources::shaders_manager::{LinesShaderContext, PolylineShaderContext};
+use polyline::Polyline;
 
 #[path = "error.rs"]
 mod error;
 
-/// Structure which manages the display of short-living lines.
+/// Structure which manages the display of short-living lines and persistent polylines.
 struct LinesManager {
     priv lines:     ~[(Vec3<GLfloat>, Vec3<GLfloat>, Vec3<GLfloat>, Vec3<GLfloat>)],
+    priv polylines: ~[Polyline],
     priv vbuf:      GLuint,
-    priv max_lines: uint
+    priv max_lines: uint,
+    priv dirty:     bool // true if the lines changed since the last upload
 }
 
 impl LinesManager {
@@ -25,8 +28,10 @@ impl LinesManager {
 
         LinesManager {
             lines:     ~[],
+            polylines: ~[],
             vbuf:      vbuf,
-            max_lines: 0
+            max_lines: 0,
+            dirty:     false
         }
     }
  
@@ -35,20 +40,87 @@ impl LinesManager {
         self.lines.len() != 0
     }
 
+    /// Indicates whether some persistent polylines have to be drawn.
+    pub fn has_polylines(&self) -> bool {
+        self.polylines.len() != 0
+    }
+
+    /// Adds a persistent polyline. It will be drawn at each frame until it is removed.
+    pub fn add_polyline(&mut self, polyline: Polyline) {
+        self.polylines.push(polyline)
+    }
+
+    /// Removes a persistent polyline.
+    pub fn remove_polyline(&mut self, polyline: &Polyline) {
+        match self.polylines.iter().rposition(|p| *polyline == *p) {
+            Some(i) => { self.polylines.swap_remove(i); },
+            None    => { }
+        }
+    }
+
+    /// The persistent polylines.
+    pub fn polylines<'r>(&'r self) -> &'r [Polyline] {
+        let res: &'r [Polyline] = self.polylines;
+
+        res
+    }
+
     /// Adds a line to be drawn during the next frame. Lines are not persistant between frames.
     /// This method must be called for each line to draw, and at each update loop iteration.
     pub fn draw_line(&mut self, a: Vec3<GLfloat>, b: Vec3<GLfloat>, color: Vec3<GLfloat>) {
         self.lines.push((a, color, b, color));
+        self.dirty = true;
+    }
+
+    /// Removes the short-living lines. This is called once per frame, after every viewport and
+    /// every camera pass has been rendered.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.dirty = true;
     }
 
-    /// Actually draws the lines.
+    /// Adds the edges of an axis-aligned box to be drawn during the next frame.
+    pub fn draw_aabb(&mut self, mins: &Vec3<GLfloat>, maxs: &Vec3<GLfloat>, color: &Vec3<GLfloat>) {
+        let corner = |i: uint| Vec3::new(
+            if i & 1 == 0 { mins.x } else { maxs.x },
+            if i & 2 == 0 { mins.y } else { maxs.y },
+            if i & 4 == 0 { mins.z } else { maxs.z });
+
+        // two corners are linked if their indices differ by exactly one bit
+        for i in range(0u, 8) {
+            for bit in [ 1u, 2, 4 ].iter() {
+                if i & *bit == 0 {
+                    self.draw_line(corner(i), corner(i | *bit), color.clone());
+                }
+            }
+        }
+    }
+
+    /// Actually draws the persistent polylines.
+    pub fn upload_polylines(&self, context: &PolylineShaderContext) {
+        // the lines width is given in pixels: the shader needs the current viewport size
+        let mut viewport: [GLint, ..4] = [ 0, 0, 0, 0 ];
+
+        unsafe { verify!(gl::GetIntegerv(gl::VIEWPORT, &mut viewport[0])); }
+        verify!(gl::Uniform2f(context.viewport, viewport[2] as GLfloat, viewport[3] as GLfloat));
+
+        for p in self.polylines.iter() {
+            p.upload(context)
+        }
+    }
+
+    /// Actually draws the lines. The lines are kept until `clear` is called, so that they can be
+    /// drawn by several viewports or camera passes.
     pub fn upload(&mut self, context: &LinesShaderContext) {
         if self.lines.len() == 0 { return }
 
         unsafe {
             verify!(gl::BindBuffer(gl::ARRAY_BUFFER, self.vbuf));
 
-            if self.lines.len() > self.max_lines {
+            if !self.dirty {
+                // the buffer already contains the lines
+            }
+            else if self.lines.len() > self.max_lines {
                 // realloc the vertex buffer
                 self.max_lines = self.lines.capacity();
 
@@ -85,6 +157,6 @@ impl LinesManager {
             verify!(gl::DrawArrays(gl::LINES, 0, (self.lines.len() * 2) as i32));
         }
 
-        self.lines.clear();
+        self.dirty = false;
     }
 }
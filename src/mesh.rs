@@ -6,29 +6,38 @@ use std::mem;
 use std::cast;
 use gl;
 use gl::types::*;
-use nalgebra::na::{Vec2, Vec3};
+use nalgebra::na::{Vec2, Vec3, Vec4};
 use nalgebra::na;
+use bounding_volume::{AABB, BoundingSphere};
+use mesh_ops;
 
 pub type Coord  = Vec3<GLfloat>;
 pub type Normal = Vec3<GLfloat>;
 pub type UV     = Vec2<GLfloat>;
+/// A unit tangent (`x`, `y`, `z`) and the handedness (`w`, either `1.0` or `-1.0`) of the
+/// tangent space. The bitangent is `w * cross(normal, tangent)`.
+pub type Tangent = Vec4<GLfloat>;
 pub type Vertex = GLuint;
 pub type Face   = Vec3<Vertex>;
 
 #[path = "error.rs"]
 mod error;
 
-/// A Mesh contains all geometric data of a mesh: vertex buffer, index buffer, normals and uvs.
-/// It also contains the GPU location of those buffers.
+/// A Mesh contains all geometric data of a mesh: vertex buffer, index buffer, normals, uvs and
+/// tangents. It also contains the GPU location of those buffers.
 pub struct Mesh {
-    priv coords:  ~[Coord],
-    priv faces:   ~[Face],
-    priv normals: ~[Normal],
-    priv uvs:     ~[UV],
-    priv ebuf:    GLuint,
-    priv nbuf:    GLuint,
-    priv vbuf:    GLuint,
-    priv tbuf:    GLuint
+    priv coords:   ~[Coord],
+    priv faces:    ~[Face],
+    priv normals:  ~[Normal],
+    priv uvs:      ~[UV],
+    priv tangents: ~[Tangent],
+    priv aabb:     Option<AABB>,
+    priv bsphere:  Option<BoundingSphere>,
+    priv ebuf:     GLuint,
+    priv nbuf:     GLuint,
+    priv vbuf:     GLuint,
+    priv tbuf:     GLuint,
+    priv tanbuf:   GLuint
 }
 
 impl Mesh {
@@ -49,16 +58,22 @@ impl Mesh {
             None     => vec::from_elem(coords.len(), na::zero()) // dummy uvs
         };
 
+        let tangents = compute_tangents_array(coords, faces, normals, uvs);
+
         let draw_location = if fast_modifiable { DynamicDraw } else { StaticDraw };
         Mesh {
-            ebuf:    load_buffer(faces, ElementArrayBuffer, draw_location),
-            nbuf:    load_buffer(normals, ArrayBuffer, draw_location),
-            vbuf:    load_buffer(coords, ArrayBuffer, draw_location),
-            tbuf:    load_buffer(uvs, ArrayBuffer, draw_location),
-            coords:  coords,
-            faces:   faces,
-            normals: normals,
-            uvs:     uvs
+            ebuf:     load_buffer(faces, ElementArrayBuffer, draw_location),
+            nbuf:     load_buffer(normals, ArrayBuffer, draw_location),
+            vbuf:     load_buffer(coords, ArrayBuffer, draw_location),
+            tbuf:     load_buffer(uvs, ArrayBuffer, draw_location),
+            tanbuf:   load_buffer(tangents, ArrayBuffer, draw_location),
+            coords:   coords,
+            faces:    faces,
+            normals:  normals,
+            uvs:      uvs,
+            tangents: tangents,
+            aabb:     None,
+            bsphere:  None
         }
     }
 
@@ -68,6 +83,15 @@ impl Mesh {
         upload_buffer(self.normals, self.nbuf, ArrayBuffer, StaticDraw);
         upload_buffer(self.coords, self.vbuf, ArrayBuffer, StaticDraw);
         upload_buffer(self.uvs, self.tbuf, ArrayBuffer, StaticDraw);
+        upload_buffer(self.tangents, self.tanbuf, ArrayBuffer, StaticDraw);
+    }
+
+    /// Binds this mesh tangents to a vertex attribute.
+    pub fn bind_tangents(&self, tangents: GLuint) {
+        unsafe {
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, self.tanbuf));
+            verify!(gl::VertexAttribPointer(tangents, 4, gl::FLOAT, gl::FALSE as u8, 0, ptr::null()));
+        }
     }
 
     /// Binds this mesh buffers to vertex attributes.
@@ -92,6 +116,12 @@ impl Mesh {
         verify!(gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0));
     }
 
+    #[doc(hidden)]
+    // The vertex buffer uniquely identifies the mesh on the gpu.
+    pub fn id(&self) -> GLuint {
+        self.vbuf
+    }
+
     /// Number of points needed to draw this mesh.
     pub fn num_pts(&self) -> uint {
         self.faces.len() * 3
@@ -102,6 +132,31 @@ impl Mesh {
         compute_normals(self.coords, self.faces, &mut self.normals);
     }
 
+    /// Recompute this mesh tangents. They depend on the vertices, normals and texture
+    /// coordinates.
+    pub fn recompute_tangents(&mut self) {
+        compute_tangents(self.coords, self.faces, self.normals, self.uvs, &mut self.tangents);
+    }
+
+    /// This mesh tangents.
+    pub fn tangents<'r>(&'r self) -> &'r [Tangent] {
+        let res: &'r [Tangent] = self.tangents;
+
+        res
+    }
+
+    /// Recompute this mesh normals, combining the faces normals according to `weighting`. Use
+    /// `with_normals` to get sharp edges.
+    pub fn recompute_weighted_normals(&mut self, weighting: NormalWeighting) {
+        compute_weighted_normals(self.coords, self.faces, weighting, &mut self.normals);
+    }
+
+    /// A copy of this mesh with normals generated according to `mode`. Vertices are duplicated
+    /// where the normals are discontinuous. See `mesh_ops::generate_normals`.
+    pub fn with_normals(&self, mode: mesh_ops::NormalMode, weighting: NormalWeighting) -> Mesh {
+        mesh_ops::generate_normals(self, mode, weighting)
+    }
+
     /// This mesh faces.
     pub fn faces<'r>(&'r self) -> &'r [Face] {
         let res: &'r [Face] = self.faces;
@@ -137,13 +192,59 @@ impl Mesh {
         res
     }
 
-    /// This mesh vertices coordinates.
+    /// This mesh vertices coordinates. This invalidates the cached bounding volumes.
     pub fn mut_coords<'r>(&'r mut self) -> &'r mut [Coord] {
+        self.aabb    = None;
+        self.bsphere = None;
+
         let res: &'r mut [Coord] = self.coords;
 
         res
     }
 
+    /// The axis-aligned bounding box of this mesh, in its local frame. It is cached until the
+    /// vertices are modified with `mut_coords`.
+    pub fn aabb(&mut self) -> AABB {
+        if self.aabb.is_none() {
+            self.aabb = Some(AABB::from_points(self.coords));
+        }
+
+        self.aabb.get_ref().clone()
+    }
+
+    /// A bounding sphere of this mesh, in its local frame. It is cached until the vertices are
+    /// modified with `mut_coords`.
+    pub fn bounding_sphere(&mut self) -> BoundingSphere {
+        if self.bsphere.is_none() {
+            self.bsphere = Some(BoundingSphere::from_points(self.coords));
+        }
+
+        self.bsphere.get_ref().clone()
+    }
+
+    /// A simplified copy of this mesh with about `target_faces` faces. See
+    /// `mesh_ops::decimate`.
+    pub fn decimated(&self, target_faces: uint) -> Mesh {
+        mesh_ops::decimate(self, target_faces)
+    }
+
+    /// A copy of this mesh with four times more faces. See `mesh_ops::loop_subdivide`.
+    pub fn subdivided(&self) -> Mesh {
+        mesh_ops::loop_subdivide(self)
+    }
+
+    /// A copy of this mesh where the vertices closer than `tolerance` are merged. Returns `None`
+    /// if no face remains. See `mesh_ops::weld_vertices`.
+    pub fn welded(&self, tolerance: f32) -> Option<Mesh> {
+        mesh_ops::weld_vertices(self, tolerance)
+    }
+
+    /// A copy of this mesh without its faces with an area smaller than `epsilon`. Returns `None`
+    /// if every face is degenerate. See `mesh_ops::remove_degenerate_faces`.
+    pub fn without_degenerate_faces(&self, epsilon: f32) -> Option<Mesh> {
+        mesh_ops::remove_degenerate_faces(self, epsilon)
+    }
+
     /// This mesh texture coordinates.
     pub fn uvs<'r>(&'r self) -> &'r [UV] {
         let res: &'r [UV] = self.uvs;
@@ -170,6 +271,146 @@ pub fn compute_normals_array(coordinates: &[Coord],
     res
 }
 
+/// Computes tangents from a set of faces and texture coordinates.
+pub fn compute_tangents_array(coordinates: &[Coord],
+                              faces:       &[Face],
+                              normals:     &[Normal],
+                              uvs:         &[UV])
+                              -> ~[Tangent] {
+    let mut res = ~[];
+
+    compute_tangents(coordinates, faces, normals, uvs, &mut res);
+
+    res
+}
+
+/// Computes tangents from a set of faces and texture coordinates. The tangent of a vertex is
+/// aligned with the direction of increasing `u` texture coordinate, and orthogonalized with
+/// respect to its normal. Its `w` component gives the direction of increasing `v`.
+pub fn compute_tangents(coordinates: &[Coord],
+                        faces:       &[Face],
+                        normals:     &[Normal],
+                        uvs:         &[UV],
+                        tangents:    &mut ~[Tangent]) {
+    let mut sdirs: ~[Vec3<f32>] = vec::from_elem(coordinates.len(), na::zero());
+    let mut tdirs: ~[Vec3<f32>] = vec::from_elem(coordinates.len(), na::zero());
+
+    // Accumulate the directions of increasing `u` and `v` of each face ...
+    for f in faces.iter() {
+        let edge1 = coordinates[f.y] - coordinates[f.x];
+        let edge2 = coordinates[f.z] - coordinates[f.x];
+        let duv1  = uvs[f.y] - uvs[f.x];
+        let duv2  = uvs[f.z] - uvs[f.x];
+        let det   = duv1.x * duv2.y - duv2.x * duv1.y;
+
+        if det == 0.0 {
+            // degenerate texture mapping
+            continue
+        }
+
+        let sdir = (edge1 * duv2.y - edge2 * duv1.y) / det;
+        let tdir = (edge2 * duv1.x - edge1 * duv2.x) / det;
+
+        for &v in [f.x, f.y, f.z].iter() {
+            sdirs[v] = sdirs[v] + sdir;
+            tdirs[v] = tdirs[v] + tdir;
+        }
+    }
+
+    // ... and orthogonalize them with respect to the normals.
+    tangents.clear();
+
+    for i in range(0u, coordinates.len()) {
+        let n = normals[i];
+        let mut t = sdirs[i] - n * na::dot(&n, &sdirs[i]);
+
+        if na::sqnorm(&t) == 0.0 {
+            // no usable texture coordinates: any direction orthogonal to the normal will do
+            t = if n.x.abs() < 0.9 { na::cross(&n, &Vec3::x()) } else { na::cross(&n, &Vec3::y()) };
+        }
+
+        t = na::normalize(&t);
+
+        let w = if na::dot(&na::cross(&n, &t), &tdirs[i]) < 0.0 { -1.0 } else { 1.0 };
+
+        tangents.push(Vec4::new(t.x, t.y, t.z, w));
+    }
+}
+
+/// The way the normals of the faces sharing a vertex are combined into the vertex normal.
+#[deriving(Eq, Clone, ToStr)]
+pub enum NormalWeighting {
+    /// All faces contribute equally.
+    UniformWeights,
+    /// Each face contributes proportionally to its area. Small faces (e.g. the thin faces of a
+    /// bevel) have less influence.
+    AreaWeights,
+    /// Each face contributes proportionally to its angle at the vertex. The result does not
+    /// depend on how the faces are triangulated.
+    AngleWeights
+}
+
+/// The normal of the face `f` at its vertex `v`, weighted according to `weighting`. Returns the
+/// zero vector for a degenerate face.
+pub fn weighted_face_normal(coordinates: &[Coord],
+                            f:           &Face,
+                            v:           Vertex,
+                            weighting:   NormalWeighting)
+                            -> Normal {
+    let (a, b, c) = if v == f.x {
+        (coordinates[f.x], coordinates[f.y], coordinates[f.z])
+    }
+    else if v == f.y {
+        (coordinates[f.y], coordinates[f.z], coordinates[f.x])
+    }
+    else {
+        (coordinates[f.z], coordinates[f.x], coordinates[f.y])
+    };
+
+    let edge1  = b - a;
+    let edge2  = c - a;
+    let normal = na::cross(&edge1, &edge2);
+    let norm   = na::norm(&normal);
+
+    if norm == 0.0 {
+        return na::zero()
+    }
+
+    match weighting {
+        UniformWeights => normal / norm,
+        AreaWeights    => normal / 2.0,
+        AngleWeights   => {
+            let cos = na::dot(&edge1, &edge2) / (na::norm(&edge1) * na::norm(&edge2));
+
+            normal * (cos.max(&-1.0).min(&1.0).acos() / norm)
+        }
+    }
+}
+
+/// Computes unit normals from a set of faces, combining the faces normals according to
+/// `weighting`.
+pub fn compute_weighted_normals(coordinates: &[Coord],
+                                faces:       &[Face],
+                                weighting:   NormalWeighting,
+                                normals:     &mut ~[Normal]) {
+    normals.clear();
+    normals.grow(coordinates.len(), &na::zero());
+
+    for f in faces.iter() {
+        for &v in [f.x, f.y, f.z].iter() {
+            normals[v] = normals[v] + weighted_face_normal(coordinates, f, v, weighting);
+        }
+    }
+
+    for n in normals.mut_iter() {
+        let norm = na::norm(n);
+
+        if norm != 0.0 {
+            *n = *n / norm
+        }
+    }
+}
+
 /// Comutes normals from a set of faces.
 pub fn compute_normals(coordinates: &[Coord],
                        faces:       &[Face],
@@ -263,11 +504,14 @@ pub fn load_buffer<T>(buf: &[T], buf_type: BufferType, allocation_type: Allocati
 /// Allocates and uploads a buffer to the gpu.
 pub fn upload_buffer<T>(buf: &[T], buf_id: GLuint, buf_type: BufferType, allocation_type: AllocationType) {
     unsafe {
+        // an empty mesh has empty buffers: there is no first element to point to
+        let data = if buf.is_empty() { ptr::null() } else { cast::transmute(&buf[0]) };
+
         verify!(gl::BindBuffer(buf_type.to_gl(), buf_id));
         verify!(gl::BufferData(
                 buf_type.to_gl(),
                 (buf.len() * mem::size_of::<T>()) as GLsizeiptr,
-                cast::transmute(&buf[0]),
+                data,
                 allocation_type.to_gl()));
     }
 }
@@ -279,6 +523,7 @@ impl Drop for Mesh {
             verify!(gl::DeleteBuffers(1, &self.nbuf));
             verify!(gl::DeleteBuffers(1, &self.vbuf));
             verify!(gl::DeleteBuffers(1, &self.tbuf));
+            verify!(gl::DeleteBuffers(1, &self.tanbuf));
         }
     }
 }
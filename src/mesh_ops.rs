@@ -0,0 +1,644 @@
+//! Geometric operations on meshes.
+
+use std::vec;
+use std::hashmap::{HashMap, HashSet};
+use extra::sort;
+use nalgebra::na::Vec3;
+use nalgebra::na;
+use mesh::{Mesh, Coord, UV, Face, Vertex, Normal, NormalWeighting};
+use mesh;
+
+/// The way the normals of a mesh are generated.
+#[deriving(Eq, Clone, ToStr)]
+pub enum NormalMode {
+    /// Each face has its own normal: every vertex is duplicated for each face using it.
+    FlatNormals,
+    /// Each vertex has the combined normal of all the faces sharing it.
+    SmoothNormals,
+    /// Like `SmoothNormals`, except that faces making an angle greater than the given crease
+    /// angle (in radians) do not contribute to each other normals. The vertices lying on such
+    /// sharp edges are duplicated.
+    CreaseNormals(f32)
+}
+
+/// Weight of the planes constraining the border edges during the decimation.
+static BORDER_WEIGHT: f32 = 1000.0;
+
+/// Error quadric of a vertex: sum of the squared distances to a set of planes. Only the upper
+/// triangle of the symmetric 4x4 matrix is stored.
+#[deriving(Clone)]
+struct Quadric {
+    m: [f32, ..10]
+}
+
+impl Quadric {
+    fn new() -> Quadric {
+        Quadric { m: [0.0, ..10] }
+    }
+
+    // Quadric of the plane with the unit normal `n` and passing through `p`.
+    fn from_plane(n: &Vec3<f32>, p: &Vec3<f32>, weight: f32) -> Quadric {
+        let (a, b, c) = (n.x, n.y, n.z);
+        let d         = -na::dot(n, p);
+
+        Quadric {
+            m: [ a * a * weight, a * b * weight, a * c * weight, a * d * weight,
+                 b * b * weight, b * c * weight, b * d * weight,
+                 c * c * weight, c * d * weight,
+                 d * d * weight ]
+        }
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for i in range(0u, 10) {
+            self.m[i] = self.m[i] + other.m[i]
+        }
+    }
+
+    fn error(&self, p: &Vec3<f32>) -> f32 {
+        let m         = &self.m;
+        let (x, y, z) = (p.x, p.y, p.z);
+
+        m[0] * x * x + 2.0 * m[1] * x * y + 2.0 * m[2] * x * z + 2.0 * m[3] * x +
+        m[4] * y * y + 2.0 * m[5] * y * z + 2.0 * m[6] * y +
+        m[7] * z * z + 2.0 * m[8] * z +
+        m[9]
+    }
+}
+
+/// Simplifies a mesh by collapsing its edges until it has about `target_faces` faces.
+///
+/// Edges are collapsed by increasing quadric error (see Garland and Heckbert, *Surface
+/// Simplification Using Quadric Error Metrics*). Collapses flipping a face are rejected and
+/// border edges are heavily penalized so that open borders and texture seams keep their shape.
+/// The simplification stops early if no edge can be collapsed anymore. Collapses removing more
+/// faces than needed are skipped: the result never has less than `target_faces` faces, but it may
+/// have a few more. The normals of the result are recomputed.
+pub fn decimate(mesh: &Mesh, target_faces: uint) -> Mesh {
+    assert!(target_faces > 0, "The target number of faces cannot be zero.");
+
+    let mut coords   = mesh.coords().to_owned();
+    let mut uvs      = mesh.uvs().to_owned();
+    let mut faces    = mesh.faces().to_owned();
+    let num_vertices = coords.len();
+    let mut quadrics = vertex_quadrics(coords, faces);
+
+    while faces.len() > target_faces {
+        // faces adjacent to each vertex
+        let mut vfaces = vec::from_elem(num_vertices, ~[]);
+
+        for (i, f) in faces.iter().enumerate() {
+            vfaces[f.x].push(i);
+            vfaces[f.y].push(i);
+            vfaces[f.z].push(i);
+        }
+
+        // cost of each edge collapse, with the interpolation parameter of the new vertex
+        let mut visited    = HashSet::new();
+        let mut candidates = ~[];
+
+        for f in faces.iter() {
+            for &(a, b) in [(f.x, f.y), (f.y, f.z), (f.z, f.x)].iter() {
+                if visited.insert(edge_key(a, b)) {
+                    let mut q = quadrics[a].clone();
+                    q.add(&quadrics[b]);
+
+                    let mut cost   = q.error(&coords[a]);
+                    let mut best_t = 0.0f32;
+
+                    for &t in [0.5f32, 1.0].iter() {
+                        let err = q.error(&(coords[a] * (1.0 - t) + coords[b] * t));
+
+                        if err < cost {
+                            cost   = err;
+                            best_t = t;
+                        }
+                    }
+
+                    candidates.push((cost, a, b, best_t));
+                }
+            }
+        }
+
+        sort::quick_sort(candidates, |&(c1, _, _, _), &(c2, _, _, _)| c1 <= c2);
+
+        // collapse the cheapest edges. The 1-ring of each collapsed edge is locked so that the
+        // flip tests of the next collapses of this pass remain valid.
+        let mut locked    = vec::from_elem(num_vertices, false);
+        let mut remap     = vec::from_fn(num_vertices, |i| i as Vertex);
+        let mut removed   = 0u;
+        let     to_remove = faces.len() - target_faces;
+
+        for &(_, a, b, t) in candidates.iter() {
+            if removed >= to_remove {
+                break
+            }
+
+            if locked[a] || locked[b] {
+                continue
+            }
+
+            // the faces containing both `a` and `b` degenerate
+            let mut shared = 0u;
+
+            for &fid in vfaces[a].iter() {
+                let f = faces[fid];
+
+                if f.x == b || f.y == b || f.z == b {
+                    shared = shared + 1;
+                }
+            }
+
+            // never go below the target number of faces
+            if shared > to_remove - removed {
+                continue
+            }
+
+            let pos = coords[a] * (1.0 - t) + coords[b] * t;
+
+            if collapse_flips(coords, faces, vfaces[a], a, b, &pos) ||
+               collapse_flips(coords, faces, vfaces[b], b, a, &pos) {
+                continue
+            }
+
+            for &fid in vfaces[a].iter().chain(vfaces[b].iter()) {
+                let f = faces[fid];
+
+                locked[f.x] = true;
+                locked[f.y] = true;
+                locked[f.z] = true;
+            }
+
+            removed = removed + shared;
+
+            let qb = quadrics[b].clone();
+            quadrics[a].add(&qb);
+            coords[a] = pos;
+            uvs[a]    = uvs[a] * (1.0 - t) + uvs[b] * t;
+            remap[b]  = a;
+        }
+
+        let mut new_faces = vec::with_capacity(faces.len());
+
+        for f in faces.iter() {
+            let nf = Vec3::new(remap[f.x], remap[f.y], remap[f.z]);
+
+            if nf.x != nf.y && nf.y != nf.z && nf.z != nf.x {
+                new_faces.push(nf)
+            }
+        }
+
+        if new_faces.len() == faces.len() {
+            // nothing can be collapsed anymore
+            break
+        }
+
+        faces = new_faces;
+    }
+
+    let kept = compact(num_vertices, faces);
+
+    Mesh::new(gather(coords, kept), faces, None, Some(gather(uvs, kept)), false)
+}
+
+/// Subdivides a mesh using Loop's scheme: each triangle is split into four triangles and the
+/// vertices are smoothed. Borders (and texture seams) are subdivided as cubic B-splines so that
+/// they stay shared by the faces on both sides. Texture coordinates are linearly interpolated and
+/// the normals are recomputed.
+pub fn loop_subdivide(mesh: &Mesh) -> Mesh {
+    let coords       = mesh.coords();
+    let uvs          = mesh.uvs();
+    let faces        = mesh.faces();
+    let num_vertices = coords.len();
+
+    // opposite vertices of each edge
+    let mut edges = HashMap::new();
+
+    for f in faces.iter() {
+        for &(a, b, c) in [(f.x, f.y, f.z), (f.y, f.z, f.x), (f.z, f.x, f.y)].iter() {
+            edges.find_or_insert_with(edge_key(a, b), |_| ~[]).push(c);
+        }
+    }
+
+    // odd vertices: one per edge
+    let mut new_coords = coords.to_owned();
+    let mut new_uvs    = uvs.to_owned();
+    let mut neighbors  = vec::from_elem(num_vertices, ~[]);
+    let mut borders    = vec::from_elem(num_vertices, ~[]);
+    let mut edge_ids   = HashMap::new();
+
+    for (&(a, b), opposites) in edges.iter() {
+        let pos = if opposites.len() == 2 {
+            (coords[a] + coords[b]) * (3.0 / 8.0) + (coords[opposites[0]] + coords[opposites[1]]) * (1.0 / 8.0)
+        }
+        else {
+            // border or non-manifold edge
+            borders[a].push(b);
+            borders[b].push(a);
+
+            (coords[a] + coords[b]) * 0.5
+        };
+
+        neighbors[a].push(b);
+        neighbors[b].push(a);
+
+        edge_ids.insert((a, b), new_coords.len() as Vertex);
+        new_coords.push(pos);
+        new_uvs.push((uvs[a] + uvs[b]) * 0.5);
+    }
+
+    // even vertices: the original ones, smoothed
+    for i in range(0u, num_vertices) {
+        let pos = if !borders[i].is_empty() {
+            if borders[i].len() == 2 {
+                coords[i] * (3.0 / 4.0) + (coords[borders[i][0]] + coords[borders[i][1]]) * (1.0 / 8.0)
+            }
+            else {
+                // corner
+                coords[i]
+            }
+        }
+        else if !neighbors[i].is_empty() {
+            let n    = neighbors[i].len();
+            let beta = if n == 3 { 3.0 / 16.0 } else { 3.0 / (8.0 * n as f32) };
+            let mut sum: Coord = na::zero();
+
+            for &j in neighbors[i].iter() {
+                sum = sum + coords[j];
+            }
+
+            coords[i] * (1.0 - n as f32 * beta) + sum * beta
+        }
+        else {
+            // isolated vertex
+            coords[i]
+        };
+
+        new_coords[i] = pos;
+    }
+
+    let mut new_faces = vec::with_capacity(faces.len() * 4);
+
+    for f in faces.iter() {
+        let ab = *edge_ids.get(&edge_key(f.x, f.y));
+        let bc = *edge_ids.get(&edge_key(f.y, f.z));
+        let ca = *edge_ids.get(&edge_key(f.z, f.x));
+
+        new_faces.push(Vec3::new(f.x, ab, ca));
+        new_faces.push(Vec3::new(ab, f.y, bc));
+        new_faces.push(Vec3::new(ca, bc, f.z));
+        new_faces.push(Vec3::new(ab, bc, ca));
+    }
+
+    Mesh::new(new_coords, new_faces, None, Some(new_uvs), false)
+}
+
+/// Merges the vertices closer than `tolerance` to each other. The merged vertex keeps the
+/// position and texture coordinates of the first vertex found. Faces which become degenerate
+/// are removed and the normals are recomputed. Returns `None` if no face remains.
+pub fn weld_vertices(mesh: &Mesh, tolerance: f32) -> Option<Mesh> {
+    assert!(tolerance >= 0.0, "The welding tolerance cannot be negative.");
+
+    let coords       = mesh.coords();
+    let num_vertices = coords.len();
+    let cell_size    = if tolerance > 0.0 { tolerance } else { 1.0 };
+    let sqtol        = tolerance * tolerance;
+
+    // spatial hashing: each vertex only has to be compared with the vertices of the 27 cells
+    // around it.
+    let mut grid  = HashMap::new();
+    let mut remap = vec::with_capacity(num_vertices);
+
+    for (i, p) in coords.iter().enumerate() {
+        let cell = cell_of(p, cell_size);
+
+        let target = match find_close_vertex(&grid, coords, cell, p, sqtol) {
+            Some(j) => j,
+            None    => {
+                grid.find_or_insert_with(cell, |_| ~[]).push(i as Vertex);
+
+                i as Vertex
+            }
+        };
+
+        remap.push(target);
+    }
+
+    let mut faces = ~[];
+
+    for f in mesh.faces().iter() {
+        let nf = Vec3::new(remap[f.x], remap[f.y], remap[f.z]);
+
+        if nf.x != nf.y && nf.y != nf.z && nf.z != nf.x {
+            faces.push(nf)
+        }
+    }
+
+    if faces.is_empty() {
+        return None
+    }
+
+    let kept = compact(num_vertices, faces);
+
+    Some(Mesh::new(gather(coords, kept), faces, None, Some(gather(mesh.uvs(), kept)), false))
+}
+
+/// Removes the faces with two identical vertices, or with an area smaller than `epsilon`. The
+/// vertices which are not used anymore are removed too. Normals and texture coordinates of the
+/// remaining vertices are kept. Returns `None` if every face is degenerate.
+pub fn remove_degenerate_faces(mesh: &Mesh, epsilon: f32) -> Option<Mesh> {
+    let coords    = mesh.coords();
+    let mut faces = ~[];
+
+    for f in mesh.faces().iter() {
+        if f.x == f.y || f.y == f.z || f.z == f.x {
+            continue
+        }
+
+        let area = na::norm(&face_normal(&coords[f.x], &coords[f.y], &coords[f.z])) / 2.0;
+
+        if area >= epsilon {
+            faces.push(*f)
+        }
+    }
+
+    if faces.is_empty() {
+        return None
+    }
+
+    let kept = compact(coords.len(), faces);
+
+    Some(Mesh::new(gather(coords, kept),
+                   faces,
+                   Some(gather(mesh.normals(), kept)),
+                   Some(gather(mesh.uvs(), kept)),
+                   false))
+}
+
+// Finds a vertex closer than `sqrt(sqtol)` to `p` on the grid cells around `cell`.
+fn find_close_vertex(grid:   &HashMap<(i32, i32, i32), ~[Vertex]>,
+                     coords: &[Coord],
+                     cell:   (i32, i32, i32),
+                     p:      &Coord,
+                     sqtol:  f32)
+                     -> Option<Vertex> {
+    let (cx, cy, cz) = cell;
+
+    for dx in range(-1i32, 2) {
+        for dy in range(-1i32, 2) {
+            for dz in range(-1i32, 2) {
+                match grid.find(&(cx + dx, cy + dy, cz + dz)) {
+                    Some(candidates) => {
+                        for &j in candidates.iter() {
+                            if na::sqnorm(&(coords[j] - *p)) <= sqtol {
+                                return Some(j)
+                            }
+                        }
+                    },
+                    None => { }
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Generates the normals of a mesh. With `FlatNormals` and `CreaseNormals`, vertices are
+/// duplicated where the normals are discontinuous.
+///
+/// Faces are considered adjacent only if they share vertex indices: vertices duplicated on
+/// texture seams should be welded first (see `weld_vertices`) to get smooth normals across the
+/// seams.
+pub fn generate_normals(mesh: &Mesh, mode: NormalMode, weighting: NormalWeighting) -> Mesh {
+    let coords = mesh.coords();
+    let uvs    = mesh.uvs();
+    let faces  = mesh.faces();
+
+    match mode {
+        SmoothNormals => {
+            let mut normals = ~[];
+
+            mesh::compute_weighted_normals(coords, faces, weighting, &mut normals);
+
+            Mesh::new(coords.to_owned(), faces.to_owned(), Some(normals), Some(uvs.to_owned()), false)
+        },
+        FlatNormals => {
+            let mut new_coords  = ~[];
+            let mut new_normals = ~[];
+            let mut new_uvs     = ~[];
+            let mut new_faces   = ~[];
+
+            for f in faces.iter() {
+                let n    = face_normal(&coords[f.x], &coords[f.y], &coords[f.z]);
+                let norm = na::norm(&n);
+                // degenerate faces have no direction: they get a zero normal
+                let n    = if norm != 0.0 { n / norm } else { n };
+                let id   = new_coords.len() as Vertex;
+
+                for &v in [f.x, f.y, f.z].iter() {
+                    new_coords.push(coords[v]);
+                    new_normals.push(n);
+                    new_uvs.push(uvs[v]);
+                }
+
+                new_faces.push(Vec3::new(id, id + 1, id + 2));
+            }
+
+            Mesh::new(new_coords, new_faces, Some(new_normals), Some(new_uvs), false)
+        },
+        CreaseNormals(angle) => {
+            let cos_crease = angle.cos();
+
+            // faces adjacent to each vertex, with their unit normal
+            let mut vfaces = vec::from_elem(coords.len(), ~[]);
+            let mut fnormals = ~[];
+
+            for (i, f) in faces.iter().enumerate() {
+                let n    = face_normal(&coords[f.x], &coords[f.y], &coords[f.z]);
+                let norm = na::norm(&n);
+
+                fnormals.push(if norm != 0.0 { n / norm } else { n });
+                vfaces[f.x].push(i);
+                vfaces[f.y].push(i);
+                vfaces[f.z].push(i);
+            }
+
+            // each face corner gets the combined normal of the adjacent faces which are not
+            // separated from its face by a sharp edge. Corners of the same vertex with the same
+            // normal share the same new vertex.
+            let mut new_coords  = ~[];
+            let mut new_normals = ~[];
+            let mut new_uvs     = ~[];
+            let mut new_faces   = ~[];
+            let mut splits: ~[~[(Normal, Vertex)]] = vec::from_elem(coords.len(), ~[]);
+
+            for (i, f) in faces.iter().enumerate() {
+                let mut ids = [0 as Vertex, ..3];
+
+                for (k, &v) in [f.x, f.y, f.z].iter().enumerate() {
+                    let mut n: Normal = na::zero();
+
+                    for &j in vfaces[v].iter() {
+                        if na::dot(&fnormals[i], &fnormals[j]) >= cos_crease {
+                            n = n + mesh::weighted_face_normal(coords, &faces[j], v, weighting);
+                        }
+                    }
+
+                    let norm = na::norm(&n);
+
+                    if norm != 0.0 {
+                        n = n / norm;
+                    }
+
+                    let existing = splits[v].iter().find(|&&(sn, _)| na::sqnorm(&(sn - n)) < 1.0e-10)
+                                                  .map(|&(_, id)| id);
+
+                    ids[k] = match existing {
+                        Some(id) => id,
+                        None     => {
+                            let id = new_coords.len() as Vertex;
+
+                            new_coords.push(coords[v]);
+                            new_normals.push(n);
+                            new_uvs.push(uvs[v]);
+                            splits[v].push((n, id));
+
+                            id
+                        }
+                    };
+                }
+
+                new_faces.push(Vec3::new(ids[0], ids[1], ids[2]));
+            }
+
+            Mesh::new(new_coords, new_faces, Some(new_normals), Some(new_uvs), false)
+        }
+    }
+}
+
+fn cell_of(p: &Coord, cell_size: f32) -> (i32, i32, i32) {
+    ((p.x / cell_size).floor() as i32,
+     (p.y / cell_size).floor() as i32,
+     (p.z / cell_size).floor() as i32)
+}
+
+fn edge_key(a: Vertex, b: Vertex) -> (Vertex, Vertex) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+// The quadric of each vertex is the sum of the planes of its adjacent faces, plus the planes
+// orthogonal to its adjacent border edges.
+fn vertex_quadrics(coords: &[Coord], faces: &[Face]) -> ~[Quadric] {
+    let mut quadrics   = vec::from_elem(coords.len(), Quadric::new());
+    let mut edge_faces = HashMap::new();
+
+    for (i, f) in faces.iter().enumerate() {
+        let n    = face_normal(&coords[f.x], &coords[f.y], &coords[f.z]);
+        let norm = na::norm(&n);
+
+        if norm != 0.0 {
+            let q = Quadric::from_plane(&(n / norm), &coords[f.x], 1.0);
+
+            quadrics[f.x].add(&q);
+            quadrics[f.y].add(&q);
+            quadrics[f.z].add(&q);
+        }
+
+        for &(a, b) in [(f.x, f.y), (f.y, f.z), (f.z, f.x)].iter() {
+            let entry = edge_faces.find_or_insert(edge_key(a, b), (0u, i));
+            let (count, fid) = *entry;
+
+            *entry = (count + 1, fid);
+        }
+    }
+
+    for (&(a, b), &(count, fid)) in edge_faces.iter() {
+        if count == 1 {
+            let f    = faces[fid];
+            let n    = face_normal(&coords[f.x], &coords[f.y], &coords[f.z]);
+            let edge = coords[b] - coords[a];
+            let cn   = na::cross(&edge, &n);
+            let norm = na::norm(&cn);
+
+            if norm != 0.0 {
+                let q = Quadric::from_plane(&(cn / norm), &coords[a], BORDER_WEIGHT);
+
+                quadrics[a].add(&q);
+                quadrics[b].add(&q);
+            }
+        }
+    }
+
+    quadrics
+}
+
+fn face_normal(a: &Coord, b: &Coord, c: &Coord) -> Vec3<f32> {
+    na::cross(&(*b - *a), &(*c - *a))
+}
+
+// Returns `true` if moving `v` to `pos` flips one of its adjacent faces. Faces containing `other`
+// are ignored since they are removed by the collapse.
+fn collapse_flips(coords: &[Coord],
+                  faces:  &[Face],
+                  vfaces: &[uint],
+                  v:      Vertex,
+                  other:  Vertex,
+                  pos:    &Coord)
+                  -> bool {
+    fn moved(coords: &[Coord], i: Vertex, v: Vertex, pos: &Coord) -> Coord {
+        if i == v { *pos } else { coords[i] }
+    }
+
+    for &fid in vfaces.iter() {
+        let f = faces[fid];
+
+        if f.x == other || f.y == other || f.z == other {
+            continue
+        }
+
+        let before = face_normal(&coords[f.x], &coords[f.y], &coords[f.z]);
+        let after  = face_normal(&moved(coords, f.x, v, pos),
+                                 &moved(coords, f.y, v, pos),
+                                 &moved(coords, f.z, v, pos));
+
+        if na::dot(&before, &after) <= 0.0 {
+            return true
+        }
+    }
+
+    false
+}
+
+// Removes the vertices not referenced by any face, and updates the faces accordingly. Returns
+// the old index of each remaining vertex.
+fn compact(num_vertices: uint, faces: &mut [Face]) -> ~[Vertex] {
+    let mut ids  = vec::from_elem(num_vertices, None);
+    let mut kept = ~[];
+
+    for f in faces.mut_iter() {
+        f.x = compact_vertex(f.x, &mut ids, &mut kept);
+        f.y = compact_vertex(f.y, &mut ids, &mut kept);
+        f.z = compact_vertex(f.z, &mut ids, &mut kept);
+    }
+
+    kept
+}
+
+fn compact_vertex(v: Vertex, ids: &mut ~[Option<Vertex>], kept: &mut ~[Vertex]) -> Vertex {
+    match ids[v] {
+        Some(id) => id,
+        None     => {
+            let id = kept.len() as Vertex;
+
+            kept.push(v);
+            ids[v] = Some(id);
+
+            id
+        }
+    }
+}
+
+fn gather<T: Clone>(values: &[T], ids: &[Vertex]) -> ~[T] {
+    ids.iter().map(|&i| values[i].clone()).collect()
+}
@@ -20,19 +20,45 @@ enum Mode {
     Unknown
 }
 
-fn error(line: uint, err: &str) -> ! {
-    fail!("At line " + line.to_str() + ": " + err)
+fn error(line: uint, err: &str) -> ~str {
+    "At line " + line.to_str() + ": " + err
 }
 
-/// Parses an obj file.
+/// Parses an obj file. Fails if the file cannot be read or is not a valid obj file.
 pub fn parse_file(path: &str) -> Mesh {
-    let s   = Path::new(path).open_reader(io::Open).expect("Cannot open the file: " + path).read_to_end();
-    let obj = str::from_utf8_owned(s);
-    parse(obj)
+    match try_parse_file(path) {
+        Ok(mesh) => mesh,
+        Err(err) => fail!(err)
+    }
+}
+
+/// Parses an obj file. Returns an error message if the file cannot be read or is not a valid obj
+/// file. This is useful to reload files edited at runtime without failing if they are saved while
+/// incomplete.
+pub fn try_parse_file(path: &str) -> Result<Mesh, ~str> {
+    let s = match Path::new(path).open_reader(io::Open) {
+        Some(mut reader) => reader.read_to_end(),
+        None             => return Err("Cannot open the file: " + path)
+    };
+
+    if !str::is_utf8(s) {
+        return Err("The file is not valid utf8: " + path)
+    }
+
+    try_parse(str::from_utf8_owned(s))
 }
 
 /// Parses a string representing an obj file and returns (vertices, normals, texture coordinates, indices)
 pub fn parse(string: &str) -> Mesh {
+    match try_parse(string) {
+        Ok(mesh) => mesh,
+        Err(err) => fail!(err)
+    }
+}
+
+/// Parses a string representing an obj file. Returns an error message if the string is not a
+/// valid obj file.
+pub fn try_parse(string: &str) -> Result<Mesh, ~str> {
     let mut coords:  ~[Coord]        = ~[];
     let mut normals: ~[Normal]       = ~[];
     let mut mesh:    ~[Vec3<GLuint>] = ~[];
@@ -63,24 +89,24 @@ pub fn parse(string: &str) -> Mesh {
                 match mode {
                     V  => match word_val {
                         Some(v) => {
-                            if i - 1 >= curr_coords.len() { error(l, "vertices must have 3 components.") }
+                            if i - 1 >= curr_coords.len() { return Err(error(l, "vertices must have 3 components.")) }
                             curr_coords.set(i - 1, v)
                         },
-                        None    => error(l, "failed to parse `" + word + "' as a GLfloat.")
+                        None    => return Err(error(l, "failed to parse `" + word + "' as a GLfloat."))
                     },
                     VN => match word_val {
                         Some(n) => {
-                            if i - 1 >= curr_normal.len() { error(l, "normals must have 3 components.") }
+                            if i - 1 >= curr_normal.len() { return Err(error(l, "normals must have 3 components.")) }
                             curr_normal.set(i - 1, n)
                         },
-                        None    => error(l, "failed to parse `" + word + "' as a GLfloat.")
+                        None    => return Err(error(l, "failed to parse `" + word + "' as a GLfloat."))
                     },
                     VT => match word_val {
                         Some(t) => {
-                            if i - 1 >= curr_tex.len() { error(l, "texture coordinates must have 2 components.") }
+                            if i - 1 >= curr_tex.len() { return Err(error(l, "texture coordinates must have 2 components.")) }
                             curr_tex.set(i - 1, t)
                         },
-                        None    => error(l, "failed to parse `" + word + "' as a GLfloat.")
+                        None    => return Err(error(l, "failed to parse `" + word + "' as a GLfloat."))
                     },
                     F  => {
                         // Four formats possible:
@@ -101,7 +127,7 @@ pub fn parse(string: &str) -> Mesh {
                                 let idx: Option<GLuint> = FromStr::from_str(w);
                                 match idx {
                                     Some(id) => curr_ids.set(i, id - 1),
-                                    None     => error(l, "failed to parse `" + w + "' as a GLuint.")
+                                    None     => return Err(error(l, "failed to parse `" + w + "' as a GLuint."))
                                 }
                             }
                         }
@@ -126,10 +152,10 @@ pub fn parse(string: &str) -> Mesh {
 
         if num_parsed != 0 {
             match mode {
-                V  => if num_parsed != 3 { error(l, "vertices must have 3 components.") },
-                VN => if num_parsed != 3 { error(l, "normals must have 3 components.")  },
-                F  => if num_parsed < 3 { error(l, "faces must have at least 3 vertices.") },
-                VT => if num_parsed != 2 { error(l, "texture coordinates must have 2 components.") },
+                V  => if num_parsed != 3 { return Err(error(l, "vertices must have 3 components.")) },
+                VN => if num_parsed != 3 { return Err(error(l, "normals must have 3 components.")) },
+                F  => if num_parsed < 3 { return Err(error(l, "faces must have at least 3 vertices.")) },
+                VT => if num_parsed != 2 { return Err(error(l, "texture coordinates must have 2 components.")) },
                 _  => { }
             }
         }
@@ -172,11 +198,19 @@ pub fn parse(string: &str) -> Mesh {
         println("Warning: some normals are missing. Dropping normals infos for every vertex.");
     }
 
-    reformat(
+    for v in mesh.iter() {
+        if v.x as uint >= coords.len() ||
+           (!ignore_uvs && v.y as uint >= uvs.len()) ||
+           (!ignore_normals && v.z as uint >= normals.len()) {
+            return Err(~"a face references an undefined vertex, normal or texture coordinate.")
+        }
+    }
+
+    Ok(reformat(
         coords,
         if ignore_normals { None } else { Some(normals) },
         if ignore_uvs { None } else { Some(uvs) },
-        mesh)
+        mesh))
 }
 
 fn reformat(coords:  ~[Coord],
@@ -6,26 +6,166 @@ use std::borrow;
 use std::rc::{RcMut, Rc};
 use gl;
 use gl::types::*;
-use nalgebra::na::{Mat3, Mat4, Vec3, Iso3, Rotation, Rotate, Translation, Transformation};
+use nalgebra::na::{Mat3, Mat4, Vec2, Vec3, Iso3, Rotation, Rotate, Translation, Transformation};
 use nalgebra::na;
 use resources::shaders_manager::ObjectShaderContext;
 use resources::textures_manager;
-use resources::textures_manager::Texture;
+use resources::textures_manager::{Texture, TextureParams};
 use mesh::Mesh;
+use mesh_ops;
+use bounding_volume::{AABB, BoundingSphere};
 
 #[path = "error.rs"]
 mod error;
 
+/// The way the lighting is interpolated on the faces of an object.
+#[deriving(Eq, Clone, ToStr)]
+pub enum Shading {
+    /// Each face has a uniform lighting, computed from its geometric normal. The mesh normals are
+    /// ignored.
+    Flat,
+    /// The lighting is interpolated from the mesh normals.
+    Smooth
+}
+
 type Transform3d = Iso3<f32>;
 type Scale3d     = Mat3<GLfloat>;
 
 /// Set of datas identifying a scene node.
 pub struct ObjectData {
-    priv texture:   Rc<Texture>,
+    priv textures:  ~[Option<Rc<Texture>>],   // predefined slots, indexed by texture unit
+    priv custom_textures: ~[(~str, Rc<Texture>)], // bound to the units following the predefined ones
     priv scale:     Scale3d,
     priv transform: Transform3d,
     priv color:     Vec3<f32>,
-    priv visible:   bool
+    priv uv_scale:  Vec2<f32>,
+    priv uv_offset: Vec2<f32>,
+    priv visible:   bool,
+    priv layers:    u32,
+    priv show_aabb: bool,
+    priv shading:   Shading,
+    priv reflectivity: f32,
+    priv lods:      ~[(f32, RcMut<Mesh>)], // sorted by increasing distance
+    priv lod:       uint                   // 0 for the original mesh, `i` for `lods[i - 1]`
+}
+
+impl ObjectData {
+    // The scale matrix is diagonal: its diagonal gives the scaling factor along each axis.
+    fn scale_factors(&self) -> Vec3<f32> {
+        self.scale * Vec3::new(1.0f32, 1.0, 1.0)
+    }
+
+    // The mesh of the currently selected level of detail.
+    fn current_mesh(&self, original: &RcMut<Mesh>) -> RcMut<Mesh> {
+        if self.lod == 0 {
+            original.clone()
+        }
+        else {
+            let (_, ref mesh) = self.lods[self.lod - 1];
+
+            mesh.clone()
+        }
+    }
+}
+
+/// Layer mask matching every object layer.
+pub static ALL_LAYERS: u32 = 0xffffffff;
+
+/// Number of texture units used by the object shader.
+static NUM_TEXTURE_UNITS: uint = 4;
+/// Texture unit of the diffuse texture.
+static DIFFUSE_UNIT: uint = 0;
+/// Texture unit of the normal map.
+static NORMAL_UNIT: uint = 1;
+/// Texture unit of the specular map.
+static SPECULAR_UNIT: uint = 2;
+/// Texture unit of the emissive map.
+static EMISSIVE_UNIT: uint = 3;
+/// Texture unit of the environment cube map, shared by all the objects.
+static ENVIRONMENT_UNIT: uint = 4;
+/// Texture unit of the first custom texture slot.
+static FIRST_CUSTOM_UNIT: uint = 5;
+/// Names of the texture slots used by the object shader, indexed by texture unit.
+static PREDEFINED_SLOTS: [&'static str, ..NUM_TEXTURE_UNITS] = [ "diffuse", "normal", "specular", "emissive" ];
+
+#[doc(hidden)]
+// Binds the cube map reflected by all the objects. `0` disables the reflections.
+pub fn bind_environment_map(context: &ObjectShaderContext, cube_map: GLuint) {
+    verify!(gl::Uniform1i(context.use_environment_map, (cube_map != 0) as GLint));
+    verify!(gl::ActiveTexture(gl::TEXTURE0 + ENVIRONMENT_UNIT as GLuint));
+    verify!(gl::BindTexture(gl::TEXTURE_CUBE_MAP, cube_map));
+    verify!(gl::ActiveTexture(gl::TEXTURE0));
+}
+
+// Texture unit of a predefined texture slot.
+fn predefined_unit(name: &str) -> Option<uint> {
+    PREDEFINED_SLOTS.iter().position(|n| *n == name)
+}
+
+// Texture of an empty predefined texture slot. `None` disables the slot.
+fn default_slot_texture(unit: uint) -> Option<Rc<Texture>> {
+    if unit == DIFFUSE_UNIT {
+        textures_manager::singleton().get("default")
+    }
+    else if unit == SPECULAR_UNIT || unit == EMISSIVE_UNIT {
+        textures_manager::singleton().get("default_black")
+    }
+    else {
+        None
+    }
+}
+
+fn bind_sampler(program: GLuint, name: &str, unit: uint, texture: &Rc<Texture>) {
+    verify!(gl::ActiveTexture(gl::TEXTURE0 + unit as GLuint));
+    verify!(gl::BindTexture(gl::TEXTURE_2D, texture.borrow().id()));
+
+    let location = unsafe {
+        gl::GetUniformLocation(program, (name.to_owned() + "_map").to_c_str().unwrap())
+    };
+
+    if location != -1 {
+        verify!(gl::Uniform1i(location, unit as GLint));
+    }
+}
+
+/// The gpu states set by the last drawn object. It lets consecutive objects sharing the same
+/// mesh or texture skip the redundant `BindBuffer` and `BindTexture` calls.
+#[doc(hidden)]
+pub struct ObjectRenderState {
+    priv mesh:     GLuint,                          // id of the last bound mesh, 0 if none
+    priv textures: [GLuint, ..NUM_TEXTURE_UNITS]    // id of the texture bound to each unit, 0 if none
+}
+
+impl ObjectRenderState {
+    #[doc(hidden)]
+    pub fn new() -> ObjectRenderState {
+        ObjectRenderState {
+            mesh:     0,
+            textures: [0, ..NUM_TEXTURE_UNITS]
+        }
+    }
+
+    fn bind_texture(&mut self, unit: uint, texture: GLuint) {
+        if self.textures[unit] != texture {
+            verify!(gl::ActiveTexture(gl::TEXTURE0 + unit as GLuint));
+            verify!(gl::BindTexture(gl::TEXTURE_2D, texture));
+            self.textures[unit] = texture;
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn unbind(&mut self) {
+        if self.mesh != 0 {
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, 0));
+            verify!(gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0));
+        }
+
+        // other renderers expect the first texture unit to be active
+        verify!(gl::ActiveTexture(gl::TEXTURE0));
+
+        self.mesh     = 0;
+        self.textures = [0, ..NUM_TEXTURE_UNITS];
+    }
 }
 
 /// Structure of all 3d objects on the scene. This is the only interface to manipulate the object
@@ -52,8 +192,20 @@ impl Object {
                                  0.0, 0.0, sz),
             transform: na::one(),
             color:     Vec3::new(r, g, b),
-            texture:   texture,
-            visible:   true
+            uv_scale:  Vec2::new(1.0, 1.0),
+            uv_offset: Vec2::new(0.0, 0.0),
+            textures:  ~[ Some(texture),
+                          None,
+                          default_slot_texture(SPECULAR_UNIT),
+                          default_slot_texture(EMISSIVE_UNIT) ],
+            custom_textures: ~[],
+            visible:   true,
+            layers:    1,
+            show_aabb: false,
+            shading:   Smooth,
+            reflectivity: 0.0,
+            lods:      ~[],
+            lod:       0
         };
 
         Object {
@@ -63,7 +215,7 @@ impl Object {
     }
 
     #[doc(hidden)]
-    pub fn upload(&self, context: &ObjectShaderContext) {
+    pub fn upload(&self, context: &ObjectShaderContext, state: &mut ObjectRenderState) {
         do self.data.with_borrow |data| {
             if data.visible {
                 let formated_transform:  Mat4<f32> = na::to_homogeneous(&data.transform);
@@ -85,23 +237,134 @@ impl Object {
 
                     verify!(gl::Uniform3f(context.color, data.color.x, data.color.y, data.color.z));
 
-                    // FIXME: we should not switch the buffers if the last drawn shape uses the same.
-                    self.mesh.with_borrow(|m| m.bind(context.pos, context.normal, context.tex_coord));
+                    verify!(gl::Uniform1i(context.flat_shading, (data.shading == Flat) as GLint));
+
+                    verify!(gl::Uniform2f(context.uv_scale, data.uv_scale.x, data.uv_scale.y));
+                    verify!(gl::Uniform2f(context.uv_offset, data.uv_offset.x, data.uv_offset.y));
+
+                    verify!(gl::Uniform1f(context.reflectivity, data.reflectivity));
 
-                    verify!(gl::ActiveTexture(gl::TEXTURE0));
-                    verify!(gl::BindTexture(gl::TEXTURE_2D, self.data.with_borrow(|d| d.texture.borrow().id())));
+                    let mesh = data.current_mesh(&self.mesh);
+
+                    do mesh.with_borrow |m| {
+                        if state.mesh != m.id() {
+                            m.bind(context.pos, context.normal, context.tex_coord);
+                            m.bind_tangents(context.tangent);
+                            state.mesh = m.id();
+                        }
+                    }
+
+                    for (unit, texture) in data.textures.iter().enumerate() {
+                        match *texture {
+                            Some(ref t) => state.bind_texture(unit, t.borrow().id()),
+                            None        => { }
+                        }
+                    }
+
+                    verify!(gl::Uniform1i(context.use_normal_map, data.textures[NORMAL_UNIT].is_some() as GLint));
 
                     verify!(gl::DrawElements(gl::TRIANGLES,
-                                             self.mesh.with_borrow(|m| m.num_pts()) as GLint,
+                                             mesh.with_borrow(|m| m.num_pts()) as GLint,
                                              gl::UNSIGNED_INT,
                                              ptr::null()));
+                }
+            }
+        }
+    }
+
+    #[doc(hidden)]
+    // Key used to sort the render queue such that objects sharing the same mesh and texture are
+    // drawn consecutively.
+    pub fn render_key(&self) -> (GLuint, GLuint) {
+        do self.data.with_borrow |d| {
+            let mesh = d.current_mesh(&self.mesh).with_borrow(|m| m.id());
 
-                    self.mesh.with_borrow(|m| m.unbind());
+            (mesh, d.textures[DIFFUSE_UNIT].get_ref().borrow().id())
+        }
+    }
+
+    #[doc(hidden)]
+    // All the textures used by this object.
+    pub fn textures(&self) -> ~[Rc<Texture>] {
+        do self.data.with_borrow |d| {
+            let mut res: ~[Rc<Texture>] = d.textures.iter().filter_map(|t| t.clone()).collect();
+
+            for &(_, ref t) in d.custom_textures.iter() {
+                res.push(t.clone());
+            }
+
+            res
+        }
+    }
+
+    #[doc(hidden)]
+    // Selects the level of detail to use given the position of the camera.
+    pub fn select_lod(&self, eye: &Vec3<f32>) {
+        if self.data.with_borrow(|d| d.lods.is_empty()) {
+            return
+        }
+
+        let bsphere = self.world_bounding_sphere();
+        let dist    = na::norm(&(*bsphere.center() - *eye));
+
+        do self.data.with_mut_borrow |d| {
+            d.lod = 0;
+
+            for (i, &(min_dist, _)) in d.lods.iter().enumerate() {
+                if dist >= min_dist {
+                    d.lod = i + 1;
                 }
             }
         }
     }
 
+    /// Adds a level of detail to this object. The mesh `lod` will be drawn instead of the object
+    /// original mesh when the object is at least `min_dist` away from the camera. If several
+    /// levels match, the one with the greatest `min_dist` is used.
+    ///
+    /// The bounding volumes and `modify_mesh` only consider the original mesh.
+    pub fn add_lod(&mut self, lod: RcMut<Mesh>, min_dist: f32) {
+        let mut lod = Some(lod);
+
+        do self.data.with_mut_borrow |d| {
+            let pos = d.lods.iter().position(|&(dist, _)| dist > min_dist).unwrap_or(d.lods.len());
+
+            d.lods.insert(pos, (min_dist, lod.take_unwrap()));
+        }
+    }
+
+    /// Automatically builds levels of detail by simplifying this object mesh.
+    ///
+    /// # Arguments
+    ///   * `levels` - a list of `(ratio, min_dist)`. For each element, a simplified mesh with
+    ///   `ratio` times the number of faces of the original mesh is added as a level of detail
+    ///   used when the object is at least `min_dist` away from the camera.
+    pub fn generate_lods(&mut self, levels: &[(f32, f32)]) {
+        let num_faces = self.mesh.with_borrow(|m| m.faces().len());
+
+        for &(ratio, min_dist) in levels.iter() {
+            assert!(ratio > 0.0 && ratio <= 1.0, "The ratio of faces kept must be in ]0.0, 1.0].");
+
+            let target = ((num_faces as f32 * ratio) as uint).max(&1);
+            let lod    = self.mesh.with_borrow(|m| mesh_ops::decimate(m, target));
+
+            self.add_lod(RcMut::from_send(lod), min_dist);
+        }
+    }
+
+    /// Removes all the levels of detail of this object. Its original mesh is always used.
+    pub fn clear_lods(&mut self) {
+        do self.data.with_mut_borrow |d| {
+            d.lods.clear();
+            d.lod = 0;
+        }
+    }
+
+    /// The number of levels of detail of this object, not counting its original mesh.
+    pub fn num_lods(&self) -> uint {
+        self.data.with_borrow(|d| d.lods.len())
+    }
+
     /// Sets the visible state of this object. An invisible object does not draw itself.
     pub fn set_visible(&mut self, visible: bool) {
         self.data.with_mut_borrow(|d| d.visible = visible)
@@ -112,6 +375,69 @@ impl Object {
         self.data.with_borrow(|d| d.visible)
     }
 
+    /// Sets the layers this object belongs to, as a bit mask. A viewport or
+    /// `Window::render_to_texture` draws this object only if its layer mask shares at least one
+    /// bit with `layers`. By default, an object belongs to the first layer only, i-e. `layers` is
+    /// `1`.
+    pub fn set_layers(&mut self, layers: u32) {
+        self.data.with_mut_borrow(|d| d.layers = layers)
+    }
+
+    /// The layers this object belongs to, as a bit mask.
+    pub fn layers(&self) -> u32 {
+        self.data.with_borrow(|d| d.layers)
+    }
+
+    /// Sets the way the lighting is interpolated on the faces of this object. The default value is
+    /// `Smooth`. `Flat` shading does not need the mesh to be modified, so it is the cheapest way
+    /// to display faceted objects.
+    pub fn set_shading(&mut self, shading: Shading) {
+        self.data.with_mut_borrow(|d| d.shading = shading)
+    }
+
+    /// The way the lighting is interpolated on the faces of this object.
+    pub fn shading(&self) -> Shading {
+        self.data.with_borrow(|d| d.shading)
+    }
+
+    /// Sets how much this object reflects the environment, from `0.0` (no reflection, the default)
+    /// to `1.0` (perfect mirror). The environment is the skybox of the window (see
+    /// `Window::set_skybox`): nothing is reflected if there is none.
+    pub fn set_reflectivity(&mut self, reflectivity: f32) {
+        self.data.with_mut_borrow(|d| d.reflectivity = reflectivity)
+    }
+
+    /// How much this object reflects the environment.
+    pub fn reflectivity(&self) -> f32 {
+        self.data.with_borrow(|d| d.reflectivity)
+    }
+
+    /// Enables or disables the display of this object axis-aligned bounding box.
+    pub fn show_bounding_box(&mut self, show: bool) {
+        self.data.with_mut_borrow(|d| d.show_aabb = show)
+    }
+
+    /// Returns true if this object axis-aligned bounding box is displayed.
+    pub fn bounding_box_shown(&self) -> bool {
+        self.data.with_borrow(|d| d.show_aabb)
+    }
+
+    /// The axis-aligned bounding box of this object, in world coordinates. It takes the object
+    /// scale and transformation into account.
+    pub fn world_aabb(&self) -> AABB {
+        let aabb = self.mesh.with_mut_borrow(|m| m.aabb());
+
+        self.data.with_borrow(|d| aabb.transformed(&d.scale_factors(), &d.transform))
+    }
+
+    /// A bounding sphere of this object, in world coordinates. It takes the object scale and
+    /// transformation into account.
+    pub fn world_bounding_sphere(&self) -> BoundingSphere {
+        let bsphere = self.mesh.with_mut_borrow(|m| m.bounding_sphere());
+
+        self.data.with_borrow(|d| bsphere.transformed(&d.scale_factors(), &d.transform))
+    }
+
     /// Sets the local scaling factor of the object.
     pub fn set_scale(&mut self, sx: f32, sy: f32, sz: f32) {
         do self.data.with_mut_borrow |d| {
@@ -123,10 +449,11 @@ impl Object {
     }
 
     /// Get a write access to the geometry mesh. Return true if the geometry needs to be
-    /// re-uploaded to the GPU.
+    /// re-uploaded to the GPU. The mesh tangents are recomputed before the upload.
     pub fn modify_mesh(&mut self, f: &fn(&mut Mesh) -> bool) {
         do self.mesh.with_mut_borrow |m| {
             if f(m) {
+                m.recompute_tangents();
                 // FIXME: find a way to upload only the modified parts.
                 m.upload()
             }
@@ -147,7 +474,149 @@ impl Object {
     /// # Arguments
     ///   * `path` - relative path of the texture on the disk
     pub fn set_texture(&mut self, path: &str) {
-        self.data.with_mut_borrow(|d| d.texture = textures_manager::singleton().add(path));
+        self.set_texture_slot_from_file("diffuse", path)
+    }
+
+    /// Sets the texture of the object, with the given sampling parameters.
+    ///
+    /// # Arguments
+    ///   * `path` - relative path of the texture on the disk
+    ///   * `params` - the texture wrap mode, filtering, mipmaps and anisotropy
+    pub fn set_texture_with_params(&mut self, path: &str, params: &TextureParams) {
+        self.set_texture_slot("diffuse", textures_manager::singleton().add(path, params))
+    }
+
+    /// Sets the factors the texture coordinates of this object are multiplied by. Values greater
+    /// than `1.0` tile the textures, provided their wrap mode is `Repeat` (see
+    /// `textures_manager::TextureParams`). The default value is `(1.0, 1.0)`.
+    pub fn set_uv_scale(&mut self, u: f32, v: f32) {
+        self.data.with_mut_borrow(|d| d.uv_scale = Vec2::new(u, v))
+    }
+
+    /// The factors the texture coordinates of this object are multiplied by.
+    pub fn uv_scale(&self) -> Vec2<f32> {
+        self.data.with_borrow(|d| d.uv_scale)
+    }
+
+    /// Sets the offset added to the texture coordinates of this object, after they are scaled.
+    /// Animating it scrolls the textures. The default value is `(0.0, 0.0)`.
+    pub fn set_uv_offset(&mut self, u: f32, v: f32) {
+        self.data.with_mut_borrow(|d| d.uv_offset = Vec2::new(u, v))
+    }
+
+    /// The offset added to the texture coordinates of this object.
+    pub fn uv_offset(&self) -> Vec2<f32> {
+        self.data.with_borrow(|d| d.uv_offset)
+    }
+
+    /// Sets the normal map of the object. It gives, for each texel, the normal of the surface in
+    /// tangent space (i-e. relative to the mesh normals and tangents).
+    ///
+    /// # Arguments
+    ///   * `path` - relative path of the normal map on the disk
+    pub fn set_normal_map(&mut self, path: &str) {
+        self.set_texture_slot_from_file("normal", path)
+    }
+
+    /// Removes the normal map of the object. The lighting is computed from the mesh normals only.
+    pub fn remove_normal_map(&mut self) {
+        self.remove_texture_slot("normal")
+    }
+
+    /// Sets the texture of one of the texture slots of this object.
+    ///
+    /// The slots `"diffuse"`, `"normal"`, `"specular"` and `"emissive"` are used by the default
+    /// shader and bound to the texture units 0 to 3. Any other name defines a custom slot, bound
+    /// to the next free texture unit, and only useful for user-defined shaders (see
+    /// `bind_textures`).
+    pub fn set_texture_slot(&mut self, name: &str, texture: Rc<Texture>) {
+        let mut texture = Some(texture);
+
+        do self.data.with_mut_borrow |d| {
+            match predefined_unit(name) {
+                Some(unit) => d.textures[unit] = texture.take(),
+                None       => {
+                    match d.custom_textures.iter().position(|&(ref n, _)| n.as_slice() == name) {
+                        Some(i) => d.custom_textures[i] = (name.to_owned(), texture.take_unwrap()),
+                        None    => d.custom_textures.push((name.to_owned(), texture.take_unwrap()))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sets the texture of one of the texture slots of this object from a file. See
+    /// `set_texture_slot`.
+    ///
+    /// # Arguments
+    ///   * `name` - the slot name
+    ///   * `path` - relative path of the texture on the disk
+    pub fn set_texture_slot_from_file(&mut self, name: &str, path: &str) {
+        self.set_texture_slot(name, textures_manager::singleton().add(path, &TextureParams::new()))
+    }
+
+    /// Empties a texture slot. The `"diffuse"` slot falls back to a white texture, the
+    /// `"specular"` and `"emissive"` slots to a black texture. Custom slots are removed and the
+    /// texture units of the following custom slots are shifted.
+    pub fn remove_texture_slot(&mut self, name: &str) {
+        do self.data.with_mut_borrow |d| {
+            match predefined_unit(name) {
+                Some(unit) => d.textures[unit] = default_slot_texture(unit),
+                None       => {
+                    match d.custom_textures.iter().position(|&(ref n, _)| n.as_slice() == name) {
+                        Some(i) => { d.custom_textures.remove(i); },
+                        None    => { }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The texture of one of the texture slots of this object.
+    pub fn texture_slot(&self, name: &str) -> Option<Rc<Texture>> {
+        do self.data.with_borrow |d| {
+            match predefined_unit(name) {
+                Some(unit) => d.textures[unit].clone(),
+                None       => d.custom_textures.iter().find(|&&(ref n, _)| n.as_slice() == name)
+                                                      .map(|&(_, ref t)| t.clone())
+            }
+        }
+    }
+
+    /// The texture unit a texture slot of this object is bound to.
+    pub fn texture_unit(&self, name: &str) -> Option<uint> {
+        match predefined_unit(name) {
+            Some(unit) => Some(unit),
+            None       => {
+                do self.data.with_borrow |d| {
+                    d.custom_textures.iter().position(|&(ref n, _)| n.as_slice() == name)
+                                     .map(|i| FIRST_CUSTOM_UNIT + i)
+                }
+            }
+        }
+    }
+
+    /// Binds the textures of all the slots of this object to their texture unit, and sets the
+    /// sampler uniforms of `program` accordingly. The sampler of a slot must be named after the
+    /// slot with a `_map` suffix, e.g. `diffuse_map` or `roughness_map`. Missing samplers are
+    /// ignored.
+    ///
+    /// This is meant to be used by user-defined shaders: `program` must be the current program.
+    pub fn bind_textures(&self, program: GLuint) {
+        do self.data.with_borrow |d| {
+            for (unit, texture) in d.textures.iter().enumerate() {
+                match *texture {
+                    Some(ref t) => bind_sampler(program, PREDEFINED_SLOTS[unit], unit, t),
+                    None        => { }
+                }
+            }
+
+            for (i, &(ref name, ref t)) in d.custom_textures.iter().enumerate() {
+                bind_sampler(program, *name, FIRST_CUSTOM_UNIT + i, t)
+            }
+        }
+
+        verify!(gl::ActiveTexture(gl::TEXTURE0));
     }
 
     /// Move and orient the object such that it is placed at the point `eye` and have its `x` axis
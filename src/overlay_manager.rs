@@ -0,0 +1,156 @@
+use std::ptr;
+use std::cast;
+use std::mem;
+use gl;
+use gl::types::*;
+use nalgebra::na::{Vec2, Vec3};
+use resources::shaders_manager::OverlayShaderContext;
+
+#[path = "error.rs"]
+mod error;
+
+/// Number of floats per vertex: position (2) and color (3).
+static VERTEX_SIZE: uint = 5;
+
+/// Structure which manages the display of short-living 2d primitives drawn on top of the scene.
+struct OverlayManager {
+    priv lines:     ~[GLfloat],
+    priv triangles: ~[GLfloat],
+    priv vbuf:      GLuint,
+    priv max_len:   uint
+}
+
+impl OverlayManager {
+    /// Creates a new overlay manager.
+    pub fn new() -> OverlayManager {
+        let mut vbuf: GLuint = 0;
+
+        unsafe { verify!(gl::GenBuffers(1, &mut vbuf)) };
+
+        OverlayManager {
+            lines:     ~[],
+            triangles: ~[],
+            vbuf:      vbuf,
+            max_len:   0
+        }
+    }
+
+    /// Indicates whether some primitives have to be drawn.
+    pub fn needs_rendering(&self) -> bool {
+        self.lines.len() != 0 || self.triangles.len() != 0
+    }
+
+    /// Adds a 2d line to be drawn during the next frame.
+    pub fn draw_line(&mut self, a: &Vec2<GLfloat>, b: &Vec2<GLfloat>, color: &Vec3<GLfloat>) {
+        push_vertex(&mut self.lines, a, color);
+        push_vertex(&mut self.lines, b, color);
+    }
+
+    /// Adds a 2d triangle to be drawn during the next frame.
+    pub fn draw_triangle(&mut self,
+                         a:     &Vec2<GLfloat>,
+                         b:     &Vec2<GLfloat>,
+                         c:     &Vec2<GLfloat>,
+                         color: &Vec3<GLfloat>) {
+        push_vertex(&mut self.triangles, a, color);
+        push_vertex(&mut self.triangles, b, color);
+        push_vertex(&mut self.triangles, c, color);
+    }
+
+    /// Adds the border of a polygon to be drawn during the next frame.
+    pub fn draw_polygon_border(&mut self, points: &[Vec2<GLfloat>], color: &Vec3<GLfloat>) {
+        for i in range(0u, points.len()) {
+            self.draw_line(&points[i], &points[(i + 1) % points.len()], color)
+        }
+    }
+
+    /// Adds a filled convex polygon to be drawn during the next frame. It is triangulated as a
+    /// triangle fan.
+    pub fn draw_filled_polygon(&mut self, points: &[Vec2<GLfloat>], color: &Vec3<GLfloat>) {
+        if points.len() < 3 { return }
+
+        for i in range(1u, points.len() - 1) {
+            self.draw_triangle(&points[0], &points[i], &points[i + 1], color)
+        }
+    }
+
+    /// Actually draws the 2d primitives. They are drawn on top of everything else.
+    pub fn upload(&mut self, context: &OverlayShaderContext, width: f32, height: f32) {
+        if !self.needs_rendering() { return }
+
+        verify!(gl::Disable(gl::DEPTH_TEST));
+        verify!(gl::Uniform2f(context.viewport, width, height));
+
+        let triangles_len = self.triangles.len();
+        self.triangles.push_all(self.lines);
+
+        unsafe {
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, self.vbuf));
+
+            if self.triangles.len() > self.max_len {
+                // realloc the vertex buffer
+                self.max_len = self.triangles.capacity();
+
+                verify!(gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    (self.max_len * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                    ptr::null(),
+                    gl::STREAM_DRAW));
+            }
+
+            verify!(gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                0,
+                (self.triangles.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                cast::transmute(&self.triangles[0])));
+
+            verify!(gl::VertexAttribPointer(
+                context.pos,
+                2,
+                gl::FLOAT,
+                gl::FALSE as u8,
+                (VERTEX_SIZE * mem::size_of::<GLfloat>()) as GLint,
+                ptr::null()));
+
+            verify!(gl::VertexAttribPointer(
+                context.color,
+                3,
+                gl::FLOAT,
+                gl::FALSE as u8,
+                (VERTEX_SIZE * mem::size_of::<GLfloat>()) as GLint,
+                cast::transmute(2 * mem::size_of::<GLfloat>())));
+
+            let num_triangle_vertices = triangles_len / VERTEX_SIZE;
+            let num_line_vertices     = self.lines.len() / VERTEX_SIZE;
+
+            if num_triangle_vertices != 0 {
+                verify!(gl::DrawArrays(gl::TRIANGLES, 0, num_triangle_vertices as GLint));
+            }
+
+            if num_line_vertices != 0 {
+                verify!(gl::DrawArrays(gl::LINES, num_triangle_vertices as GLint, num_line_vertices as GLint));
+            }
+
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, 0));
+        }
+
+        verify!(gl::Enable(gl::DEPTH_TEST));
+
+        self.lines.clear();
+        self.triangles.clear();
+    }
+}
+
+fn push_vertex(out: &mut ~[GLfloat], p: &Vec2<GLfloat>, color: &Vec3<GLfloat>) {
+    out.push(p.x);
+    out.push(p.y);
+    out.push(color.x);
+    out.push(color.y);
+    out.push(color.z);
+}
+
+impl Drop for OverlayManager {
+    fn drop(&mut self) {
+        unsafe { verify!(gl::DeleteBuffers(1, &self.vbuf)); }
+    }
+}
@@ -0,0 +1,339 @@
+//! Data structure of a persistent polyline.
+
+use std::ptr;
+use std::cast;
+use std::mem;
+use std::vec;
+use std::borrow;
+use std::util;
+use std::rc::RcMut;
+use gl;
+use gl::types::*;
+use nalgebra::na::Vec3;
+use nalgebra::na;
+use resources::shaders_manager::PolylineShaderContext;
+
+#[path = "error.rs"]
+mod error;
+
+/// Number of floats per vertex: position (3), other segment end (3), side (1), distance (1).
+static VERTEX_SIZE: uint = 8;
+/// Number of vertices needed to draw one segment as a screen-space quad.
+static VERTICES_PER_SEGMENT: uint = 6;
+
+/// The way the points of a polyline are connected together.
+#[deriving(Eq, Clone, ToStr)]
+pub enum PolylineMode {
+    /// Each point is linked to the next one.
+    LineStrip,
+    /// Each point is linked to the next one, and the last point is linked to the first one.
+    LineLoop,
+    /// Points are taken two by two, each pair forming an independent segment.
+    LineSegments
+}
+
+/// The pattern used to draw a polyline.
+#[deriving(Eq, Clone, ToStr)]
+pub enum LineStyle {
+    /// A continuous line.
+    Solid,
+    /// A dashed line. The first argument is the length of a dash, the second is the length of
+    /// the gap between two dashes. Both are expressed in world units.
+    Dashed(f32, f32)
+}
+
+/// Set of datas identifying a polyline.
+pub struct PolylineData {
+    priv points:       ~[Vec3<f32>],
+    priv mode:         PolylineMode,
+    priv style:        LineStyle,
+    priv color:        Vec3<f32>,
+    priv width:        f32,
+    priv visible:      bool,
+    priv vbuf:         GLuint,
+    priv capacity:     uint, // in number of segments
+    priv num_uploaded: uint, // in number of segments
+    priv last_dist:    f32,  // distance along the line at the end of the last uploaded segment
+    priv dirty:        bool  // true if everything has to be re-uploaded
+}
+
+/// A polyline living on the scene until it is explicitly removed. Contrary to
+/// `Window::draw_line`, its vertices are kept on the gpu between frames: appending points to a
+/// strip only uploads the new segments.
+#[deriving(Clone)]
+pub struct Polyline {
+    priv data: RcMut<PolylineData>
+}
+
+impl Polyline {
+    #[doc(hidden)]
+    pub fn new(points: ~[Vec3<f32>], mode: PolylineMode, r: f32, g: f32, b: f32) -> Polyline {
+        let mut vbuf: GLuint = 0;
+
+        unsafe { verify!(gl::GenBuffers(1, &mut vbuf)) };
+
+        let data = PolylineData {
+            points:       points,
+            mode:         mode,
+            style:        Solid,
+            color:        Vec3::new(r, g, b),
+            width:        1.0,
+            visible:      true,
+            vbuf:         vbuf,
+            capacity:     0,
+            num_uploaded: 0,
+            last_dist:    0.0,
+            dirty:        true
+        };
+
+        Polyline {
+            data: RcMut::new(data)
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn upload(&self, context: &PolylineShaderContext) {
+        do self.data.with_mut_borrow |data| {
+            if data.visible {
+                data.update_buffer();
+
+                let num_segments = data.num_segments();
+
+                if num_segments != 0 {
+                    let (dash, gap) = match data.style {
+                        Solid        => (0.0, 0.0),
+                        Dashed(d, g) => (d, g)
+                    };
+
+                    verify!(gl::Uniform3f(context.color, data.color.x, data.color.y, data.color.z));
+                    verify!(gl::Uniform1f(context.width, data.width));
+                    verify!(gl::Uniform2f(context.dash, dash, gap));
+
+                    let stride = (VERTEX_SIZE * mem::size_of::<GLfloat>()) as GLint;
+
+                    unsafe {
+                        verify!(gl::BindBuffer(gl::ARRAY_BUFFER, data.vbuf));
+                        verify!(gl::VertexAttribPointer(context.pos, 3, gl::FLOAT, gl::FALSE as u8, stride,
+                                                        ptr::null()));
+                        verify!(gl::VertexAttribPointer(context.other, 3, gl::FLOAT, gl::FALSE as u8, stride,
+                                                        cast::transmute(3 * mem::size_of::<GLfloat>())));
+                        verify!(gl::VertexAttribPointer(context.side, 1, gl::FLOAT, gl::FALSE as u8, stride,
+                                                        cast::transmute(6 * mem::size_of::<GLfloat>())));
+                        verify!(gl::VertexAttribPointer(context.dist, 1, gl::FLOAT, gl::FALSE as u8, stride,
+                                                        cast::transmute(7 * mem::size_of::<GLfloat>())));
+
+                        verify!(gl::DrawArrays(gl::TRIANGLES, 0, (num_segments * VERTICES_PER_SEGMENT) as GLint));
+                        verify!(gl::BindBuffer(gl::ARRAY_BUFFER, 0));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sets the visible state of this polyline. An invisible polyline does not draw itself.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.data.with_mut_borrow(|d| d.visible = visible)
+    }
+
+    /// Returns true if this polyline can be visible.
+    pub fn visible(&self) -> bool {
+        self.data.with_borrow(|d| d.visible)
+    }
+
+    /// Sets the color of the polyline. Colors components must be on the range `[0.0, 1.0]`.
+    pub fn set_color(&mut self, r: f32, g: f32, b: f32) {
+        do self.data.with_mut_borrow |d| {
+            d.color.x = r;
+            d.color.y = g;
+            d.color.z = b;
+        }
+    }
+
+    /// Sets the width of the polyline, in pixels. The default value is 1.0.
+    pub fn set_width(&mut self, width: f32) {
+        self.data.with_mut_borrow(|d| d.width = width)
+    }
+
+    /// The width of the polyline, in pixels.
+    pub fn width(&self) -> f32 {
+        self.data.with_borrow(|d| d.width)
+    }
+
+    /// Sets the pattern used to draw the polyline.
+    pub fn set_style(&mut self, style: LineStyle) {
+        self.data.with_mut_borrow(|d| d.style = style)
+    }
+
+    /// The pattern used to draw the polyline.
+    pub fn style(&self) -> LineStyle {
+        self.data.with_borrow(|d| d.style)
+    }
+
+    /// Sets the way the points of this polyline are linked together.
+    pub fn set_mode(&mut self, mode: PolylineMode) {
+        do self.data.with_mut_borrow |d| {
+            if d.mode != mode {
+                d.mode  = mode;
+                d.dirty = true;
+            }
+        }
+    }
+
+    /// The way the points of this polyline are linked together.
+    pub fn mode(&self) -> PolylineMode {
+        self.data.with_borrow(|d| d.mode)
+    }
+
+    /// The number of points of this polyline.
+    pub fn num_points(&self) -> uint {
+        self.data.with_borrow(|d| d.points.len())
+    }
+
+    /// Appends a point at the end of the polyline. With the `LineStrip` mode, only the new
+    /// segment is sent to the gpu, making this the cheap way to grow trajectory trails.
+    pub fn push_point(&mut self, point: Vec3<f32>) {
+        do self.data.with_mut_borrow |d| {
+            d.points.push(point);
+
+            if d.mode == LineLoop {
+                // the closing segment changed
+                d.dirty = true;
+            }
+        }
+    }
+
+    /// Replaces all the points of this polyline.
+    pub fn set_points(&mut self, points: ~[Vec3<f32>]) {
+        let mut points = points;
+
+        do self.data.with_mut_borrow |d| {
+            util::swap(&mut d.points, &mut points);
+            d.dirty = true;
+        }
+    }
+
+    /// Get a write access to the points of this polyline. They will be entirely re-uploaded to
+    /// the gpu during the next frame.
+    pub fn modify_points(&mut self, f: &fn(&mut ~[Vec3<f32>])) {
+        do self.data.with_mut_borrow |d| {
+            f(&mut d.points);
+            d.dirty = true;
+        }
+    }
+}
+
+impl PolylineData {
+    fn num_segments(&self) -> uint {
+        let n = self.points.len();
+
+        match self.mode {
+            LineStrip    => if n < 2 { 0 } else { n - 1 },
+            LineLoop     => if n < 2 { 0 } else if n == 2 { 1 } else { n },
+            LineSegments => n / 2
+        }
+    }
+
+    fn segment(&self, i: uint) -> (Vec3<f32>, Vec3<f32>) {
+        match self.mode {
+            LineStrip    => (self.points[i], self.points[i + 1]),
+            LineLoop     => (self.points[i], self.points[(i + 1) % self.points.len()]),
+            LineSegments => (self.points[2 * i], self.points[2 * i + 1])
+        }
+    }
+
+    fn update_buffer(&mut self) {
+        let num_segments = self.num_segments();
+
+        if num_segments > self.capacity {
+            // realloc the vertex buffer
+            self.capacity = num_segments * 2;
+
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, self.vbuf));
+            unsafe {
+                verify!(gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    (self.capacity * VERTICES_PER_SEGMENT * VERTEX_SIZE * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                    ptr::null(),
+                    gl::DYNAMIC_DRAW));
+            }
+
+            self.dirty = true;
+        }
+
+        if self.dirty {
+            self.num_uploaded = 0;
+            self.last_dist    = 0.0;
+            self.dirty        = false;
+        }
+
+        if self.num_uploaded >= num_segments {
+            return
+        }
+
+        let mut vertices = vec::with_capacity((num_segments - self.num_uploaded) * VERTICES_PER_SEGMENT * VERTEX_SIZE);
+
+        for i in range(self.num_uploaded, num_segments) {
+            let (a, b) = self.segment(i);
+
+            if self.mode == LineSegments {
+                // each segment has its own dash pattern
+                self.last_dist = 0.0;
+            }
+
+            let dist_a = self.last_dist;
+            let dist_b = dist_a + na::norm(&(b - a));
+
+            push_segment(&mut vertices, &a, &b, dist_a, dist_b);
+
+            self.last_dist = dist_b;
+        }
+
+        verify!(gl::BindBuffer(gl::ARRAY_BUFFER, self.vbuf));
+        unsafe {
+            verify!(gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                (self.num_uploaded * VERTICES_PER_SEGMENT * VERTEX_SIZE * mem::size_of::<GLfloat>()) as GLintptr,
+                (vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                cast::transmute(&vertices[0])));
+        }
+        verify!(gl::BindBuffer(gl::ARRAY_BUFFER, 0));
+
+        self.num_uploaded = num_segments;
+    }
+}
+
+// Each segment is a quad made of two triangles. The vertex shader pushes each vertex away from
+// the segment, on the screen, by half the line width. The `side` of the vertices lying on `b` is
+// inverted because their `other` end (`a`) makes the screen-space normal point the other way.
+fn push_segment(out: &mut ~[GLfloat], a: &Vec3<f32>, b: &Vec3<f32>, dist_a: f32, dist_b: f32) {
+    push_vertex(out, a, b, 1.0, dist_a);
+    push_vertex(out, a, b, -1.0, dist_a);
+    push_vertex(out, b, a, -1.0, dist_b);
+
+    push_vertex(out, b, a, -1.0, dist_b);
+    push_vertex(out, a, b, -1.0, dist_a);
+    push_vertex(out, b, a, 1.0, dist_b);
+}
+
+fn push_vertex(out: &mut ~[GLfloat], p: &Vec3<f32>, other: &Vec3<f32>, side: f32, dist: f32) {
+    out.push(p.x);
+    out.push(p.y);
+    out.push(p.z);
+    out.push(other.x);
+    out.push(other.y);
+    out.push(other.z);
+    out.push(side);
+    out.push(dist);
+}
+
+impl Eq for Polyline {
+    fn eq(&self, other: &Polyline) -> bool {
+        self.data.with_borrow(|d1| other.data.with_borrow(|d2| borrow::ref_eq(d1, d2)))
+    }
+}
+
+impl Drop for PolylineData {
+    fn drop(&mut self) {
+        unsafe { verify!(gl::DeleteBuffers(1, &self.vbuf)); }
+    }
+}
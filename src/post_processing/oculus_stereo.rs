@@ -16,6 +16,9 @@ use post_processing::post_processing_effect::PostProcessingEffect;
 #[path = "../error.rs"]
 mod error;
 
+static VERTEX_SHADER_PATH:   &'static str = "oculus_vertex_shader.glsl";
+static FRAGMENT_SHADER_PATH: &'static str = "oculus_fragment_shader.glsl";
+
 fn load_file(path: &str) -> ~str {
     let s = Path::new(path).open_reader(io::Open).expect("Cannot open the file: " + path).read_to_end();
     str::from_utf8_owned(s)
@@ -65,32 +68,61 @@ impl OculusStereo {
 
             let (program, vshader, fshader) =
                 ShadersManager::load_shader_program(
-                    load_file("oculus_vertex_shader.glsl"),
-                    load_file("oculus_fragment_shader.glsl"));
-
-            verify!(gl::UseProgram(program));
+                    load_file(VERTEX_SHADER_PATH),
+                    load_file(FRAGMENT_SHADER_PATH));
 
-            let v_coord = gl::GetAttribLocation(program, "v_coord".to_c_str().unwrap());
-
-            OculusStereo {
-                vshader:      vshader,
-                fshader:      fshader,
-                program:      program,
+            let mut res = OculusStereo {
+                vshader:      0,
+                fshader:      0,
+                program:      0,
                 time:         0.0,
-                fbo_texture:  gl::GetUniformLocation(program, "fbo_texture".to_c_str().unwrap()) as GLuint,
+                fbo_texture:  0,
                 fbo_vertices: vbo_fbo_vertices,
-                v_coord:      v_coord,
-                kappa_0:  gl::GetUniformLocation(program, "kappa_0".to_c_str().unwrap()) as GLuint,
-                kappa_1:  gl::GetUniformLocation(program, "kappa_1".to_c_str().unwrap()) as GLuint,
-                kappa_2:  gl::GetUniformLocation(program, "kappa_2".to_c_str().unwrap()) as GLuint,
-                kappa_3:  gl::GetUniformLocation(program, "kappa_3".to_c_str().unwrap()) as GLuint,
-                scale:    gl::GetUniformLocation(program, "Scale".to_c_str().unwrap()) as GLuint,
-                scale_in: gl::GetUniformLocation(program, "ScaleIn".to_c_str().unwrap()) as GLuint,
+                v_coord:      0,
+                kappa_0:      0,
+                kappa_1:      0,
+                kappa_2:      0,
+                kappa_3:      0,
+                scale:        0,
+                scale_in:     0,
                 h:  1f32, // will be updated in the first update
                 w:  1f32, // ditto
-            }
+            };
+
+            res.set_program(program, vshader, fshader);
+
+            res
         }
     }
+
+    // Replaces the shader program, and retrieves its variables locations.
+    fn set_program(&mut self, program: GLuint, vshader: GLuint, fshader: GLuint) {
+        if self.program != 0 {
+            self.delete_program();
+        }
+
+        verify!(gl::UseProgram(program));
+
+        unsafe {
+            self.vshader     = vshader;
+            self.fshader     = fshader;
+            self.program     = program;
+            self.v_coord     = gl::GetAttribLocation(program, "v_coord".to_c_str().unwrap());
+            self.fbo_texture = gl::GetUniformLocation(program, "fbo_texture".to_c_str().unwrap()) as GLuint;
+            self.kappa_0     = gl::GetUniformLocation(program, "kappa_0".to_c_str().unwrap()) as GLuint;
+            self.kappa_1     = gl::GetUniformLocation(program, "kappa_1".to_c_str().unwrap()) as GLuint;
+            self.kappa_2     = gl::GetUniformLocation(program, "kappa_2".to_c_str().unwrap()) as GLuint;
+            self.kappa_3     = gl::GetUniformLocation(program, "kappa_3".to_c_str().unwrap()) as GLuint;
+            self.scale       = gl::GetUniformLocation(program, "Scale".to_c_str().unwrap()) as GLuint;
+            self.scale_in    = gl::GetUniformLocation(program, "ScaleIn".to_c_str().unwrap()) as GLuint;
+        }
+    }
+
+    fn delete_program(&mut self) {
+        gl::DeleteProgram(self.program);
+        gl::DeleteShader(self.vshader);
+        gl::DeleteShader(self.fshader);
+    }
 }
 
 impl PostProcessingEffect for OculusStereo {
@@ -143,13 +175,24 @@ impl PostProcessingEffect for OculusStereo {
         gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4);
         verify!(gl::DisableVertexAttribArray(self.v_coord as GLuint));
     }
+
+    fn shader_files(&self) -> ~[~str] {
+        ~[ VERTEX_SHADER_PATH.to_owned(), FRAGMENT_SHADER_PATH.to_owned() ]
+    }
+
+    fn reload_shaders(&mut self) {
+        match ShadersManager::try_load_shader_program(load_file(VERTEX_SHADER_PATH),
+                                                      load_file(FRAGMENT_SHADER_PATH)) {
+            Ok((program, vshader, fshader)) => self.set_program(program, vshader, fshader),
+            // keep the previous shaders until the error is fixed
+            Err(log) => println("Failed to reload the oculus shaders: " + log)
+        }
+    }
 }
 
 impl Drop for OculusStereo {
     fn drop(&mut self) {
-        gl::DeleteProgram(self.program);
-        gl::DeleteShader(self.vshader);
-        gl::DeleteShader(self.fshader);
+        self.delete_program();
         unsafe { gl::DeleteBuffers(1, &self.fbo_vertices); }
     }
 }
@@ -15,4 +15,12 @@ pub trait PostProcessingEffect {
     ///     * `fbo_texture` - id to the texture containing the last scene drawn.
     ///     * `fbo_depth` - the depth buffer as a texture.
     fn draw(&self, shaders_manager: &mut ShadersManager, target: &RenderTarget);
+    /// The files the shaders of this effect are read from at runtime. They are watched for
+    /// modifications when hot reloading is enabled (see `Window::enable_hot_reloading`).
+    fn shader_files(&self) -> ~[~str] {
+        ~[]
+    }
+    /// Reloads the shaders of this effect. Called when one of its `shader_files` is modified.
+    fn reload_shaders(&mut self) {
+    }
 }
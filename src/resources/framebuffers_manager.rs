@@ -1,9 +1,11 @@
 //! Resource manager to allocate and switch between framebuffers.
 
 use std::ptr;
+use std::rc::Rc;
 use std::util::NonCopyable;
 use gl;
 use gl::types::*;
+use resources::textures_manager::Texture;
 
 #[path = "../error.rs"]
 mod error;
@@ -17,8 +19,10 @@ pub enum RenderTarget {
 }
 
 struct OffscreenBuffers {
-    texture: GLuint,
+    texture: Rc<Texture>,
     depth:   GLuint,
+    width:   uint,
+    height:  uint,
     ncpy:    NonCopyable
 }
 
@@ -27,7 +31,25 @@ impl RenderTarget {
     pub fn texture_id(&self) -> GLuint {
         match *self {
             Screen           => 0,
-            Offscreen(ref o) => o.texture
+            Offscreen(ref o) => o.texture.borrow().id()
+        }
+    }
+
+    /// The offscreen color buffer. It can be used as the texture of an object (see
+    /// `Object::set_texture_slot`), and remains valid after the render target is dropped.
+    /// Returns `None` for the screen.
+    pub fn texture(&self) -> Option<Rc<Texture>> {
+        match *self {
+            Screen           => None,
+            Offscreen(ref o) => Some(o.texture.clone())
+        }
+    }
+
+    /// The width and height of the offscreen buffers, in pixels. Returns `None` for the screen.
+    pub fn size(&self) -> Option<(uint, uint)> {
+        match *self {
+            Screen           => None,
+            Offscreen(ref o) => Some((o.width, o.height))
         }
     }
 
@@ -45,9 +67,12 @@ impl RenderTarget {
             Screen => {
                 verify!(gl::Viewport(0, 0, w as i32, h as i32));
             },
-            Offscreen(ref o) => {
+            Offscreen(ref mut o) => {
+                o.width  = w as uint;
+                o.height = h as uint;
+
                 // Update the fbo
-                verify!(gl::BindTexture(gl::TEXTURE_2D, o.texture));
+                verify!(gl::BindTexture(gl::TEXTURE_2D, o.texture.borrow().id()));
                 unsafe {
                     verify!(gl::TexImage2D(gl::TEXTURE_2D, 0, gl::RGBA as GLint, w as GLint, h as GLint, 0,
                     gl::RGBA, gl::UNSIGNED_BYTE, ptr::null()));
@@ -96,13 +121,12 @@ impl FramebuffersManager {
     /// Creates a new render target. A render target is the combination of a color buffer and a
     /// depth buffer.
     pub fn new_render_target(width: uint, height: uint) -> RenderTarget {
-        let mut fbo_texture: GLuint = 0;
-        let mut fbo_depth:   GLuint = 0;
+        let fbo_texture = Texture::new();
+        let mut fbo_depth: GLuint = 0;
 
         /* Texture */
         verify!(gl::ActiveTexture(gl::TEXTURE0));
-        unsafe { verify!(gl::GenTextures(1, &mut fbo_texture)); }
-        verify!(gl::BindTexture(gl::TEXTURE_2D, fbo_texture));
+        verify!(gl::BindTexture(gl::TEXTURE_2D, fbo_texture.borrow().id()));
         verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint));
         verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint));
         verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint));
@@ -127,7 +151,16 @@ impl FramebuffersManager {
         }
         verify!(gl::BindTexture(gl::TEXTURE_2D, 0));
 
-        Offscreen(OffscreenBuffers { texture: fbo_texture, depth: fbo_depth, ncpy: NonCopyable::new() })
+        // the depth texture was bound to the second unit
+        verify!(gl::ActiveTexture(gl::TEXTURE0));
+
+        Offscreen(OffscreenBuffers {
+            texture: fbo_texture,
+            depth:   fbo_depth,
+            width:   width,
+            height:  height,
+            ncpy:    NonCopyable::new()
+        })
     }
 
     /// Returns the render target associated with the screen.
@@ -146,13 +179,15 @@ impl FramebuffersManager {
             Offscreen(ref o) => {
                 self.do_select(self.fbo);
 
-                if self.curr_color != o.texture {
+                let color = o.texture.borrow().id();
+
+                if self.curr_color != color {
                     verify!(gl::FramebufferTexture2D(gl::FRAMEBUFFER,
                                                      gl::COLOR_ATTACHMENT0,
                                                      gl::TEXTURE_2D,
-                                                     o.texture,
+                                                     color,
                                                      0));
-                    self.curr_color = o.texture;
+                    self.curr_color = color;
                 }
 
                 if self.curr_depth != o.depth {
@@ -185,8 +220,8 @@ impl Drop for FramebuffersManager {
 }
 
 impl Drop for OffscreenBuffers {
+    // the color texture is released with its last reference
     fn drop(&mut self) {
-        unsafe { verify!(gl::DeleteBuffers(1, &self.texture)); }
-        unsafe { verify!(gl::DeleteBuffers(1, &self.depth)); }
+        unsafe { verify!(gl::DeleteTextures(1, &self.depth)); }
     }
 }
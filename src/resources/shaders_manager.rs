@@ -13,6 +13,12 @@ mod error;
 pub enum Shader {
     ObjectShader,
     LinesShader,
+    PolylineShader,
+    OverlayShader,
+    TextShader,
+    InstancedObjectShader,
+    SkyboxShader,
+    BackgroundShader,
     Other // FIXME: improve the manager to handler user-defined shaders properly
 }
 
@@ -21,6 +27,12 @@ impl Eq for Shader {
         match (*self, *other) {
             (ObjectShader, ObjectShader) => true,
             (LinesShader, LinesShader)   => true,
+            (PolylineShader, PolylineShader) => true,
+            (OverlayShader, OverlayShader)   => true,
+            (TextShader, TextShader)         => true,
+            (InstancedObjectShader, InstancedObjectShader) => true,
+            (SkyboxShader, SkyboxShader)     => true,
+            (BackgroundShader, BackgroundShader) => true,
             _ => false // FIXME: this is really suboptimal
         }
     }
@@ -34,13 +46,25 @@ pub struct ObjectShaderContext {
     pos:        GLuint,
     normal:     GLuint,
     tex_coord:  GLuint,
+    tangent:    GLuint,
     light:      GLint,
     color:      GLint,
     transform:  GLint,
     scale:      GLint,
     ntransform: GLint,
     view:       GLint,
-    tex:        GLint
+    eye:        GLint,
+    tex:        GLint,
+    flat_shading:   GLint,
+    normal_map:     GLint,
+    use_normal_map: GLint,
+    specular_map:   GLint,
+    emissive_map:   GLint,
+    uv_scale:       GLint,
+    uv_offset:      GLint,
+    environment_map:     GLint,
+    use_environment_map: GLint,
+    reflectivity:        GLint
 }
 
 #[doc(hidden)]
@@ -53,11 +77,95 @@ pub struct LinesShaderContext {
     view:      GLint
 }
 
+#[doc(hidden)]
+pub struct PolylineShaderContext {
+    program:  GLuint,
+    vshader:  GLuint,
+    fshader:  GLuint,
+    pos:      GLuint,
+    other:    GLuint,
+    side:     GLuint,
+    dist:     GLuint,
+    view:     GLint,
+    viewport: GLint,
+    width:    GLint,
+    color:    GLint,
+    dash:     GLint
+}
+
+#[doc(hidden)]
+pub struct OverlayShaderContext {
+    program:  GLuint,
+    vshader:  GLuint,
+    fshader:  GLuint,
+    pos:      GLuint,
+    color:    GLuint,
+    viewport: GLint
+}
+
+#[doc(hidden)]
+pub struct TextShaderContext {
+    program:   GLuint,
+    vshader:   GLuint,
+    fshader:   GLuint,
+    pos:       GLuint,
+    tex_coord: GLuint,
+    color:     GLuint,
+    viewport:  GLint,
+    font:      GLint
+}
+
+#[doc(hidden)]
+pub struct InstancedObjectShaderContext {
+    program:        GLuint,
+    vshader:        GLuint,
+    fshader:        GLuint,
+    pos:            GLuint,
+    normal:         GLuint,
+    tex_coord:      GLuint,
+    inst_transform: GLuint, // first of the four locations used by the mat4 attribute
+    inst_color:     GLuint,
+    light:          GLint,
+    scale:          GLint,
+    view:           GLint,
+    tex:            GLint
+}
+
+#[doc(hidden)]
+pub struct SkyboxShaderContext {
+    program:       GLuint,
+    vshader:       GLuint,
+    fshader:       GLuint,
+    pos:           GLuint,
+    inv_proj_view: GLint,
+    eye:           GLint,
+    skybox:        GLint
+}
+
+#[doc(hidden)]
+pub struct BackgroundShaderContext {
+    program: GLuint,
+    vshader: GLuint,
+    fshader: GLuint,
+    pos:     GLuint,
+    mode:    GLint,
+    color1:  GLint,
+    color2:  GLint,
+    tex:     GLint,
+    tiling:  GLint
+}
+
 /// The shaders manager can load the default shaders and user-provided shaders. It is the main path
 /// to select a specific shader befor rendering.
 pub struct ShadersManager {
     priv object_context: ObjectShaderContext,
     priv lines_context:  LinesShaderContext,
+    priv polyline_context: PolylineShaderContext,
+    priv overlay_context:  OverlayShaderContext,
+    priv text_context:     TextShaderContext,
+    priv instanced_object_context: InstancedObjectShaderContext,
+    priv skybox_context:   SkyboxShaderContext,
+    priv background_context: BackgroundShaderContext,
     priv shader:         Shader
 }
 
@@ -71,22 +179,63 @@ impl ShadersManager {
         ShadersManager {
             object_context: object_context,
             lines_context:  ShadersManager::load_lines_shader(),
+            polyline_context: ShadersManager::load_polyline_shader(),
+            overlay_context:  ShadersManager::load_overlay_shader(),
+            text_context:     ShadersManager::load_text_shader(),
+            instanced_object_context: ShadersManager::load_instanced_object_shader(),
+            skybox_context:   ShadersManager::load_skybox_shader(),
+            background_context: ShadersManager::load_background_shader(),
             shader:         Other
         }
     }
 
-    /// Selects a specific shader program.
+    /// Selects a specific shader program. Nothing is done if the shader is already selected,
+    /// except for `Other` which is always re-selected since it stands for any user-defined
+    /// program.
     pub fn select(&mut self, shader: Shader) {
-        if true { // FIXME: shader != self.shader
+        if shader != self.shader {
             match self.shader {
                 ObjectShader => {
                     verify!(gl::DisableVertexAttribArray(self.object_context.pos));
                     verify!(gl::DisableVertexAttribArray(self.object_context.normal));
                     verify!(gl::DisableVertexAttribArray(self.object_context.tex_coord));
+                    verify!(gl::DisableVertexAttribArray(self.object_context.tangent));
                 },
                 LinesShader => {
                     verify!(gl::DisableVertexAttribArray(self.lines_context.pos));
                     verify!(gl::DisableVertexAttribArray(self.lines_context.color));
+                },
+                PolylineShader => {
+                    verify!(gl::DisableVertexAttribArray(self.polyline_context.pos));
+                    verify!(gl::DisableVertexAttribArray(self.polyline_context.other));
+                    verify!(gl::DisableVertexAttribArray(self.polyline_context.side));
+                    verify!(gl::DisableVertexAttribArray(self.polyline_context.dist));
+                },
+                OverlayShader => {
+                    verify!(gl::DisableVertexAttribArray(self.overlay_context.pos));
+                    verify!(gl::DisableVertexAttribArray(self.overlay_context.color));
+                },
+                TextShader => {
+                    verify!(gl::DisableVertexAttribArray(self.text_context.pos));
+                    verify!(gl::DisableVertexAttribArray(self.text_context.tex_coord));
+                    verify!(gl::DisableVertexAttribArray(self.text_context.color));
+                },
+                InstancedObjectShader => {
+                    let ctxt = &self.instanced_object_context;
+
+                    verify!(gl::DisableVertexAttribArray(ctxt.pos));
+                    verify!(gl::DisableVertexAttribArray(ctxt.normal));
+                    verify!(gl::DisableVertexAttribArray(ctxt.tex_coord));
+                    for i in range(0u, 4) {
+                        verify!(gl::DisableVertexAttribArray(ctxt.inst_transform + i as GLuint));
+                    }
+                    verify!(gl::DisableVertexAttribArray(ctxt.inst_color));
+                },
+                SkyboxShader => {
+                    verify!(gl::DisableVertexAttribArray(self.skybox_context.pos));
+                },
+                BackgroundShader => {
+                    verify!(gl::DisableVertexAttribArray(self.background_context.pos));
                 }
                 _ => { }
             }
@@ -99,11 +248,50 @@ impl ShadersManager {
                     verify!(gl::EnableVertexAttribArray(self.object_context.pos));
                     verify!(gl::EnableVertexAttribArray(self.object_context.normal));
                     verify!(gl::EnableVertexAttribArray(self.object_context.tex_coord));
+                    verify!(gl::EnableVertexAttribArray(self.object_context.tangent));
                 },
                 LinesShader => {
                     verify!(gl::UseProgram(self.lines_context.program));
                     verify!(gl::EnableVertexAttribArray(self.lines_context.pos));
                     verify!(gl::EnableVertexAttribArray(self.lines_context.color));
+                },
+                PolylineShader => {
+                    verify!(gl::UseProgram(self.polyline_context.program));
+                    verify!(gl::EnableVertexAttribArray(self.polyline_context.pos));
+                    verify!(gl::EnableVertexAttribArray(self.polyline_context.other));
+                    verify!(gl::EnableVertexAttribArray(self.polyline_context.side));
+                    verify!(gl::EnableVertexAttribArray(self.polyline_context.dist));
+                },
+                OverlayShader => {
+                    verify!(gl::UseProgram(self.overlay_context.program));
+                    verify!(gl::EnableVertexAttribArray(self.overlay_context.pos));
+                    verify!(gl::EnableVertexAttribArray(self.overlay_context.color));
+                },
+                TextShader => {
+                    verify!(gl::UseProgram(self.text_context.program));
+                    verify!(gl::EnableVertexAttribArray(self.text_context.pos));
+                    verify!(gl::EnableVertexAttribArray(self.text_context.tex_coord));
+                    verify!(gl::EnableVertexAttribArray(self.text_context.color));
+                },
+                InstancedObjectShader => {
+                    let ctxt = &self.instanced_object_context;
+
+                    verify!(gl::UseProgram(ctxt.program));
+                    verify!(gl::EnableVertexAttribArray(ctxt.pos));
+                    verify!(gl::EnableVertexAttribArray(ctxt.normal));
+                    verify!(gl::EnableVertexAttribArray(ctxt.tex_coord));
+                    for i in range(0u, 4) {
+                        verify!(gl::EnableVertexAttribArray(ctxt.inst_transform + i as GLuint));
+                    }
+                    verify!(gl::EnableVertexAttribArray(ctxt.inst_color));
+                },
+                SkyboxShader => {
+                    verify!(gl::UseProgram(self.skybox_context.program));
+                    verify!(gl::EnableVertexAttribArray(self.skybox_context.pos));
+                },
+                BackgroundShader => {
+                    verify!(gl::UseProgram(self.background_context.program));
+                    verify!(gl::EnableVertexAttribArray(self.background_context.pos));
                 }
                 _ => { }
             }
@@ -120,6 +308,36 @@ impl ShadersManager {
         &'r self.lines_context
     }
 
+    #[doc(hidden)]
+    pub fn polyline_context<'r>(&'r self) -> &'r PolylineShaderContext {
+        &'r self.polyline_context
+    }
+
+    #[doc(hidden)]
+    pub fn overlay_context<'r>(&'r self) -> &'r OverlayShaderContext {
+        &'r self.overlay_context
+    }
+
+    #[doc(hidden)]
+    pub fn text_context<'r>(&'r self) -> &'r TextShaderContext {
+        &'r self.text_context
+    }
+
+    #[doc(hidden)]
+    pub fn instanced_object_context<'r>(&'r self) -> &'r InstancedObjectShaderContext {
+        &'r self.instanced_object_context
+    }
+
+    #[doc(hidden)]
+    pub fn skybox_context<'r>(&'r self) -> &'r SkyboxShaderContext {
+        &'r self.skybox_context
+    }
+
+    #[doc(hidden)]
+    pub fn background_context<'r>(&'r self) -> &'r BackgroundShaderContext {
+        &'r self.background_context
+    }
+
     fn load_object_shader() -> ObjectShaderContext {
         unsafe {
             // load the shader
@@ -137,15 +355,35 @@ impl ShadersManager {
                 pos:        gl::GetAttribLocation(program, "position".to_c_str().unwrap()) as GLuint,
                 normal:     gl::GetAttribLocation(program, "normal".to_c_str().unwrap()) as GLuint,
                 tex_coord:  gl::GetAttribLocation(program, "tex_coord_v".to_c_str().unwrap()) as GLuint,
+                tangent:    gl::GetAttribLocation(program, "tangent".to_c_str().unwrap()) as GLuint,
                 light:      gl::GetUniformLocation(program, "light_position".to_c_str().unwrap()),
                 color:      gl::GetUniformLocation(program, "color".to_c_str().unwrap()),
                 transform:  gl::GetUniformLocation(program, "transform".to_c_str().unwrap()),
                 scale:      gl::GetUniformLocation(program, "scale".to_c_str().unwrap()),
                 ntransform: gl::GetUniformLocation(program, "ntransform".to_c_str().unwrap()),
                 view:       gl::GetUniformLocation(program, "view".to_c_str().unwrap()),
-                tex:        gl::GetUniformLocation(program, "tex".to_c_str().unwrap())
+                eye:        gl::GetUniformLocation(program, "eye_position".to_c_str().unwrap()),
+                tex:        gl::GetUniformLocation(program, "diffuse_map".to_c_str().unwrap()),
+                flat_shading:   gl::GetUniformLocation(program, "flat_shading".to_c_str().unwrap()),
+                normal_map:     gl::GetUniformLocation(program, "normal_map".to_c_str().unwrap()),
+                use_normal_map: gl::GetUniformLocation(program, "use_normal_map".to_c_str().unwrap()),
+                specular_map:   gl::GetUniformLocation(program, "specular_map".to_c_str().unwrap()),
+                emissive_map:   gl::GetUniformLocation(program, "emissive_map".to_c_str().unwrap()),
+                uv_scale:       gl::GetUniformLocation(program, "uv_scale".to_c_str().unwrap()),
+                uv_offset:      gl::GetUniformLocation(program, "uv_offset".to_c_str().unwrap()),
+                environment_map:     gl::GetUniformLocation(program, "environment_map".to_c_str().unwrap()),
+                use_environment_map: gl::GetUniformLocation(program, "use_environment_map".to_c_str().unwrap()),
+                reflectivity:        gl::GetUniformLocation(program, "reflectivity".to_c_str().unwrap())
             };
 
+            // the diffuse texture uses the first texture unit (see `builtins::loader`), the other
+            // ones follow the order of the `Object` texture slots.
+            verify!(gl::Uniform1i(ctxt.normal_map, 1));
+            verify!(gl::Uniform1i(ctxt.specular_map, 2));
+            verify!(gl::Uniform1i(ctxt.emissive_map, 3));
+            // the environment map uses the unit following the texture slots
+            verify!(gl::Uniform1i(ctxt.environment_map, 4));
+
             ctxt
         }
     }
@@ -173,16 +411,202 @@ impl ShadersManager {
         }
     }
 
+    fn load_polyline_shader() -> PolylineShaderContext {
+        unsafe {
+            // load the shader
+            let (program, vshader, fshader) =
+                ShadersManager::load_shader_program(
+                    shaders::POLYLINE_VERTEX_SRC,
+                    shaders::POLYLINE_FRAGMENT_SRC);
+
+            verify!(gl::UseProgram(program));
+
+            let res = PolylineShaderContext {
+                program:  program,
+                vshader:  vshader,
+                fshader:  fshader,
+                pos:      gl::GetAttribLocation(program,  "position".to_c_str().unwrap()) as GLuint,
+                other:    gl::GetAttribLocation(program,  "other".to_c_str().unwrap()) as GLuint,
+                side:     gl::GetAttribLocation(program,  "side".to_c_str().unwrap()) as GLuint,
+                dist:     gl::GetAttribLocation(program,  "dist".to_c_str().unwrap()) as GLuint,
+                view:     gl::GetUniformLocation(program, "view".to_c_str().unwrap()),
+                viewport: gl::GetUniformLocation(program, "viewport".to_c_str().unwrap()),
+                width:    gl::GetUniformLocation(program, "width".to_c_str().unwrap()),
+                color:    gl::GetUniformLocation(program, "color".to_c_str().unwrap()),
+                dash:     gl::GetUniformLocation(program, "dash".to_c_str().unwrap())
+            };
+
+            res
+        }
+    }
+
+    fn load_overlay_shader() -> OverlayShaderContext {
+        unsafe {
+            // load the shader
+            let (program, vshader, fshader) =
+                ShadersManager::load_shader_program(
+                    shaders::OVERLAY_VERTEX_SRC,
+                    shaders::OVERLAY_FRAGMENT_SRC);
+
+            verify!(gl::UseProgram(program));
+
+            let res = OverlayShaderContext {
+                program:  program,
+                vshader:  vshader,
+                fshader:  fshader,
+                pos:      gl::GetAttribLocation(program,  "position".to_c_str().unwrap()) as GLuint,
+                color:    gl::GetAttribLocation(program,  "color".to_c_str().unwrap()) as GLuint,
+                viewport: gl::GetUniformLocation(program, "viewport".to_c_str().unwrap())
+            };
+
+            res
+        }
+    }
+
+    fn load_text_shader() -> TextShaderContext {
+        unsafe {
+            // load the shader
+            let (program, vshader, fshader) =
+                ShadersManager::load_shader_program(
+                    shaders::TEXT_VERTEX_SRC,
+                    shaders::TEXT_FRAGMENT_SRC);
+
+            verify!(gl::UseProgram(program));
+
+            let res = TextShaderContext {
+                program:   program,
+                vshader:   vshader,
+                fshader:   fshader,
+                pos:       gl::GetAttribLocation(program,  "position".to_c_str().unwrap()) as GLuint,
+                tex_coord: gl::GetAttribLocation(program,  "tex_coord_v".to_c_str().unwrap()) as GLuint,
+                color:     gl::GetAttribLocation(program,  "color".to_c_str().unwrap()) as GLuint,
+                viewport:  gl::GetUniformLocation(program, "viewport".to_c_str().unwrap()),
+                font:      gl::GetUniformLocation(program, "font".to_c_str().unwrap())
+            };
+
+            res
+        }
+    }
+
+    fn load_instanced_object_shader() -> InstancedObjectShaderContext {
+        unsafe {
+            // load the shader
+            let (program, vshader, fshader) =
+                ShadersManager::load_shader_program(
+                    shaders::INSTANCED_OBJECT_VERTEX_SRC,
+                    shaders::INSTANCED_OBJECT_FRAGMENT_SRC);
+
+            verify!(gl::UseProgram(program));
+
+            let res = InstancedObjectShaderContext {
+                program:        program,
+                vshader:        vshader,
+                fshader:        fshader,
+                pos:            gl::GetAttribLocation(program,  "position".to_c_str().unwrap()) as GLuint,
+                normal:         gl::GetAttribLocation(program,  "normal".to_c_str().unwrap()) as GLuint,
+                tex_coord:      gl::GetAttribLocation(program,  "tex_coord_v".to_c_str().unwrap()) as GLuint,
+                inst_transform: gl::GetAttribLocation(program,  "inst_transform".to_c_str().unwrap()) as GLuint,
+                inst_color:     gl::GetAttribLocation(program,  "inst_color".to_c_str().unwrap()) as GLuint,
+                light:          gl::GetUniformLocation(program, "light_position".to_c_str().unwrap()),
+                scale:          gl::GetUniformLocation(program, "scale".to_c_str().unwrap()),
+                view:           gl::GetUniformLocation(program, "view".to_c_str().unwrap()),
+                tex:            gl::GetUniformLocation(program, "tex".to_c_str().unwrap())
+            };
+
+            // the texture is always bound to the first texture unit
+            verify!(gl::Uniform1i(res.tex, 0));
+
+            res
+        }
+    }
+
+    fn load_skybox_shader() -> SkyboxShaderContext {
+        unsafe {
+            // load the shader
+            let (program, vshader, fshader) =
+                ShadersManager::load_shader_program(
+                    shaders::SKYBOX_VERTEX_SRC,
+                    shaders::SKYBOX_FRAGMENT_SRC);
+
+            verify!(gl::UseProgram(program));
+
+            let res = SkyboxShaderContext {
+                program:       program,
+                vshader:       vshader,
+                fshader:       fshader,
+                pos:           gl::GetAttribLocation(program,  "position".to_c_str().unwrap()) as GLuint,
+                inv_proj_view: gl::GetUniformLocation(program, "inv_proj_view".to_c_str().unwrap()),
+                eye:           gl::GetUniformLocation(program, "eye_position".to_c_str().unwrap()),
+                skybox:        gl::GetUniformLocation(program, "skybox".to_c_str().unwrap())
+            };
+
+            // the cube map is always bound to the first texture unit
+            verify!(gl::Uniform1i(res.skybox, 0));
+
+            res
+        }
+    }
+
+    fn load_background_shader() -> BackgroundShaderContext {
+        unsafe {
+            // load the shader
+            let (program, vshader, fshader) =
+                ShadersManager::load_shader_program(
+                    shaders::BACKGROUND_VERTEX_SRC,
+                    shaders::BACKGROUND_FRAGMENT_SRC);
+
+            verify!(gl::UseProgram(program));
+
+            let res = BackgroundShaderContext {
+                program: program,
+                vshader: vshader,
+                fshader: fshader,
+                pos:     gl::GetAttribLocation(program,  "position".to_c_str().unwrap()) as GLuint,
+                mode:    gl::GetUniformLocation(program, "mode".to_c_str().unwrap()),
+                color1:  gl::GetUniformLocation(program, "color1".to_c_str().unwrap()),
+                color2:  gl::GetUniformLocation(program, "color2".to_c_str().unwrap()),
+                tex:     gl::GetUniformLocation(program, "tex".to_c_str().unwrap()),
+                tiling:  gl::GetUniformLocation(program, "tiling".to_c_str().unwrap())
+            };
+
+            // the image is always bound to the first texture unit
+            verify!(gl::Uniform1i(res.tex, 0));
+
+            res
+        }
+    }
+
     /// Loads a shader program using the given source codes for the vertex and fragment shader.
     /// Fails after displaying opengl compilation errors if the shaders are invalid.
     pub fn load_shader_program(vertex_shader: &str, fragment_shader: &str) -> (GLuint, GLuint, GLuint) {
+        match ShadersManager::try_load_shader_program(vertex_shader, fragment_shader) {
+            Ok(res)  => res,
+            Err(log) => {
+                println("Shader compilation failed.");
+                fail!("Shader compilation failed: " + log)
+            }
+        }
+    }
+
+    /// Loads a shader program using the given source codes for the vertex and fragment shader.
+    /// Returns the opengl compilation errors if the shaders are invalid. This is useful to
+    /// reload shaders edited at runtime without failing on the first typo.
+    pub fn try_load_shader_program(vertex_shader: &str, fragment_shader: &str)
+                                   -> Result<(GLuint, GLuint, GLuint), ~str> {
         // Create and compile the vertex shader
         let vshader = gl::CreateShader(gl::VERTEX_SHADER);
         unsafe {
             verify!(gl::ShaderSource(vshader, 1, &vertex_shader.to_c_str().unwrap(), ptr::null()));
             verify!(gl::CompileShader(vshader));
         }
-        check_shader_error(vshader);
+
+        match shader_error(vshader) {
+            Some(log) => {
+                gl::DeleteShader(vshader);
+                return Err(log)
+            },
+            None => { }
+        }
 
         // Create and compile the fragment shader
         let fshader = gl::CreateShader(gl::FRAGMENT_SHADER);
@@ -191,7 +615,14 @@ impl ShadersManager {
             verify!(gl::CompileShader(fshader));
         }
 
-        check_shader_error(fshader);
+        match shader_error(fshader) {
+            Some(log) => {
+                gl::DeleteShader(vshader);
+                gl::DeleteShader(fshader);
+                return Err(log)
+            },
+            None => { }
+        }
 
         // Link the vertex and fragment shader into a shader program
         let program = gl::CreateProgram();
@@ -199,18 +630,18 @@ impl ShadersManager {
         verify!(gl::AttachShader(program, fshader));
         verify!(gl::LinkProgram(program));
 
-        (program, vshader, fshader)
+        Ok((program, vshader, fshader))
     }
 }
 
-fn check_shader_error(shader: GLuint) {
+// Returns the compilation log of `shader` if it failed to compile.
+fn shader_error(shader: GLuint) -> Option<~str> {
     let mut compiles: i32 = 0;
 
     unsafe{
         gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut compiles);
 
         if(compiles == 0) {
-            println("Shader compilation failed.");
             let mut info_log_len = 0;
 
             gl::GetShaderiv(shader, gl::INFO_LOG_LENGTH, &mut info_log_len);
@@ -228,9 +659,15 @@ fn check_shader_error(shader: GLuint) {
 
                 let bytes = c_str.as_bytes();
                 let bytes = bytes.slice_to(bytes.len() - 1);
-                fail!("Shader compilation failed: " + str::from_utf8(bytes));
+                Some(str::from_utf8(bytes))
+            }
+            else {
+                Some(~"no information log available.")
             }
         }
+        else {
+            None
+        }
     }
 }
 
@@ -243,5 +680,29 @@ impl Drop for ShadersManager {
         gl::DeleteProgram(self.lines_context.program);
         gl::DeleteShader(self.lines_context.fshader);
         gl::DeleteShader(self.lines_context.vshader);
+
+        gl::DeleteProgram(self.polyline_context.program);
+        gl::DeleteShader(self.polyline_context.fshader);
+        gl::DeleteShader(self.polyline_context.vshader);
+
+        gl::DeleteProgram(self.overlay_context.program);
+        gl::DeleteShader(self.overlay_context.fshader);
+        gl::DeleteShader(self.overlay_context.vshader);
+
+        gl::DeleteProgram(self.text_context.program);
+        gl::DeleteShader(self.text_context.fshader);
+        gl::DeleteShader(self.text_context.vshader);
+
+        gl::DeleteProgram(self.instanced_object_context.program);
+        gl::DeleteShader(self.instanced_object_context.fshader);
+        gl::DeleteShader(self.instanced_object_context.vshader);
+
+        gl::DeleteProgram(self.skybox_context.program);
+        gl::DeleteShader(self.skybox_context.fshader);
+        gl::DeleteShader(self.skybox_context.vshader);
+
+        gl::DeleteProgram(self.background_context.program);
+        gl::DeleteShader(self.background_context.fshader);
+        gl::DeleteShader(self.background_context.vshader);
     }
 }
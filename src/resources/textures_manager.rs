@@ -2,35 +2,379 @@
 
 use std::local_data;
 use std::cast;
-use std::hashmap::HashMap;
+use std::mem;
+use std::vec;
+use std::num::atan2;
+use std::hashmap::{HashMap, HashSet};
 use std::rc::Rc;
 use gl;
 use gl::types::*;
+use nalgebra::na::Vec3;
+use nalgebra::na;
 use stb_image::image::ImageU8;
 use stb_image::image;
+use gl_info::{gl_version_at_least, gl_has_extension};
 
 #[path = "../error.rs"]
 mod error;
 
+// From the `EXT_texture_filter_anisotropic` extension, missing from the gl bindings.
+static TEXTURE_MAX_ANISOTROPY_EXT:     GLenum = 0x84FE;
+static MAX_TEXTURE_MAX_ANISOTROPY_EXT: GLenum = 0x84FF;
+
+/// The way texture coordinates outside of the `[0.0, 1.0]` range are handled.
+#[deriving(Eq, Clone, ToStr)]
+pub enum WrapMode {
+    /// The texture is repeated. Use this to tile a texture.
+    Repeat,
+    /// The texture is repeated, every other copy being mirrored.
+    MirroredRepeat,
+    /// The coordinates are clamped: the texels on the texture borders are stretched.
+    ClampToEdge
+}
+
+/// The way texels are interpolated.
+#[deriving(Eq, Clone, ToStr)]
+pub enum FilterMode {
+    /// The nearest texel is used: the texture looks pixelated.
+    Nearest,
+    /// The nearest texels are linearly interpolated.
+    Linear
+}
+
+/// The sampling parameters of a texture.
+#[deriving(Eq, Clone, ToStr)]
+pub struct TextureParams {
+    /// The wrap mode along the `u` and `v` texture coordinates.
+    wrap:       WrapMode,
+    /// The filter used when the texture is magnified.
+    mag_filter: FilterMode,
+    /// The filter used when the texture is minified. It is also used between mipmap levels.
+    min_filter: FilterMode,
+    /// Whether mipmaps are generated and used when the texture is minified.
+    mipmaps:    bool,
+    /// The maximum degree of anisotropic filtering. `1.0` disables it. It is clamped to the
+    /// maximum value supported by the hardware, and ignored if the hardware does not support it.
+    anisotropy: f32
+}
+
+impl TextureParams {
+    /// Parameters of a texture clamped to its edges, linearly filtered, without mipmaps.
+    pub fn new() -> TextureParams {
+        TextureParams {
+            wrap:       ClampToEdge,
+            mag_filter: Linear,
+            min_filter: Linear,
+            mipmaps:    false,
+            anisotropy: 1.0
+        }
+    }
+
+    /// Parameters of a repeated texture, with mipmaps and anisotropic filtering. This is the
+    /// best choice for textures tiled over large surfaces, like the ground.
+    pub fn tiled() -> TextureParams {
+        TextureParams {
+            wrap:       Repeat,
+            mag_filter: Linear,
+            min_filter: Linear,
+            mipmaps:    true,
+            anisotropy: 16.0
+        }
+    }
+}
+
+/// The format of the pixels of a texture.
+///
+/// `Red8`, `RedF32` and `RgbaF32` need opengl 3.0. Single-channel textures are displayed as
+/// grayscale if texture swizzling is supported (opengl 3.3 or the `ARB_texture_swizzle`
+/// extension), and as shades of red otherwise.
+#[deriving(Eq, Clone, ToStr)]
+pub enum PixelFormat {
+    /// Four 8-bits components per pixel: red, green, blue and alpha.
+    Rgba8,
+    /// Three 8-bits components per pixel: red, green and blue.
+    Rgb8,
+    /// One 8-bits component per pixel. It is displayed as grayscale. Needs opengl 3.0.
+    Red8,
+    /// Four 32-bits float components per pixel: red, green, blue and alpha. Needs opengl 3.0.
+    RgbaF32,
+    /// One 32-bits float component per pixel. It is displayed as grayscale. Needs opengl 3.0.
+    RedF32
+}
+
+impl PixelFormat {
+    /// The number of bytes of one pixel with this format.
+    pub fn pixel_size(&self) -> uint {
+        match *self {
+            Rgba8   => 4,
+            Rgb8    => 3,
+            Red8    => 1,
+            RgbaF32 => 16,
+            RedF32  => 4
+        }
+    }
+
+    // (internal format, format, type) of the gl texture functions.
+    fn gl_formats(&self) -> (GLenum, GLenum, GLenum) {
+        match *self {
+            Rgba8   => (gl::RGBA8,   gl::RGBA, gl::UNSIGNED_BYTE),
+            Rgb8    => (gl::RGB8,    gl::RGB,  gl::UNSIGNED_BYTE),
+            Red8    => (gl::R8,      gl::RED,  gl::UNSIGNED_BYTE),
+            RgbaF32 => (gl::RGBA32F, gl::RGBA, gl::FLOAT),
+            RedF32  => (gl::R32F,    gl::RED,  gl::FLOAT)
+        }
+    }
+}
+
 /// A gpu texture. It contains the texture id provided by opengl and is automatically released.
 pub struct Texture {
-    priv id: GLuint
+    priv id:     GLuint,
+    priv target: GLenum // `TEXTURE_2D` or `TEXTURE_CUBE_MAP`
 }
 
 impl Texture {
-    /// Allocates a new texture on the gpu. The texture is not configured.
+    /// Allocates a new 2d texture on the gpu. The texture is not configured.
     pub fn new() -> Rc<Texture> {
+        Texture::new_with_target(gl::TEXTURE_2D)
+    }
+
+    /// Allocates a new cube map on the gpu. The cube map is not configured.
+    pub fn new_cube_map() -> Rc<Texture> {
+        Texture::new_with_target(gl::TEXTURE_CUBE_MAP)
+    }
+
+    fn new_with_target(target: GLenum) -> Rc<Texture> {
         let mut id: GLuint = 0;
 
         unsafe { verify!(gl::GenTextures(1, &mut id)); }
 
-        Rc::new(Texture { id: id })
+        Rc::new(Texture { id: id, target: target })
     }
 
     /// The opengl-provided texture id.
     pub fn id(&self) -> GLuint {
         self.id
     }
+
+    /// Returns true if this texture is a cube map.
+    pub fn is_cube_map(&self) -> bool {
+        self.target == gl::TEXTURE_CUBE_MAP
+    }
+
+    // The target to query the size and format of the first level of this texture. For cube
+    // maps, this is the first face.
+    fn level_target(&self) -> GLenum {
+        if self.is_cube_map() { gl::TEXTURE_CUBE_MAP_POSITIVE_X } else { gl::TEXTURE_2D }
+    }
+
+    /// Replaces the whole content of this texture. The texture is resized to `width x height`
+    /// pixels.
+    ///
+    /// `pixels` is read row by row, starting with the bottom row, and must contain exactly
+    /// `width * height` pixels with the format `format`. Its element type is typically `u8` for
+    /// the 8-bits formats and `f32` for the float formats.
+    pub fn upload<T>(&self, width: uint, height: uint, format: PixelFormat, pixels: &[T]) {
+        check_pixels_size(width, height, format, pixels);
+
+        let (internal_format, gl_format, gl_type) = format.gl_formats();
+
+        self.bind_for_upload();
+
+        unsafe {
+            verify!(gl::TexImage2D(gl::TEXTURE_2D, 0,
+                                   internal_format as GLint,
+                                   width as GLsizei,
+                                   height as GLsizei,
+                                   0, gl_format, gl_type,
+                                   cast::transmute(&pixels[0])));
+        }
+
+        self.end_upload();
+
+        // display single-channel textures as grayscale instead of red
+        if gl_format == gl::RED && (gl_version_at_least(3, 3) || gl_has_extension("GL_ARB_texture_swizzle")) {
+            verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_SWIZZLE_G, gl::RED as GLint));
+            verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_SWIZZLE_B, gl::RED as GLint));
+        }
+    }
+
+    /// Replaces the content of a rectangular region of this texture, without reallocating it.
+    /// This is the cheap way to stream new pixels each frame. The mipmaps, if any, are not
+    /// updated: call `set_params` again to regenerate them.
+    ///
+    /// # Arguments
+    ///   * `x` - column of the bottom-left pixel of the region
+    ///   * `y` - row of the bottom-left pixel of the region
+    ///   * `width` - width of the region, in pixels
+    ///   * `height` - height of the region, in pixels
+    ///   * `format` - format of `pixels`. Its number of components must match the one the
+    ///   texture was created with.
+    ///   * `pixels` - the `width * height` new pixels of the region, row by row
+    ///
+    /// Nothing happens if the region is empty. Fails if the region does not fit in the texture.
+    pub fn update_region<T>(&self,
+                            x:      uint,
+                            y:      uint,
+                            width:  uint,
+                            height: uint,
+                            format: PixelFormat,
+                            pixels: &[T]) {
+        if width == 0 || height == 0 {
+            return
+        }
+
+        check_pixels_size(width, height, format, pixels);
+
+        let (tex_width, tex_height) = self.size();
+
+        if x + width > tex_width || y + height > tex_height {
+            fail!("Invalid texture region: it does not fit in the " + tex_width.to_str() + "x" +
+                  tex_height.to_str() + " texture.");
+        }
+
+        let (_, gl_format, gl_type) = format.gl_formats();
+
+        self.bind_for_upload();
+
+        unsafe {
+            verify!(gl::TexSubImage2D(gl::TEXTURE_2D, 0,
+                                      x as GLint, y as GLint,
+                                      width as GLsizei, height as GLsizei,
+                                      gl_format, gl_type,
+                                      cast::transmute(&pixels[0])));
+        }
+
+        self.end_upload();
+    }
+
+    fn bind_for_upload(&self) {
+        if self.is_cube_map() {
+            fail!("Cannot upload pixels to a cube map: use `TexturesManager::add_cube_map` instead.");
+        }
+
+        verify!(gl::ActiveTexture(gl::TEXTURE0));
+        verify!(gl::BindTexture(gl::TEXTURE_2D, self.id));
+        // rows of 1 and 3 components pixels are not always 4-bytes aligned
+        verify!(gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1));
+    }
+
+    fn end_upload(&self) {
+        // restore the default alignment, expected by the other uploads
+        verify!(gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4));
+    }
+
+    /// The width and height of this texture, in pixels. For cube maps, this is the size of one
+    /// face.
+    pub fn size(&self) -> (uint, uint) {
+        let mut width:  GLint = 0;
+        let mut height: GLint = 0;
+        let level_target      = self.level_target();
+
+        verify!(gl::ActiveTexture(gl::TEXTURE0));
+        verify!(gl::BindTexture(self.target, self.id));
+
+        unsafe {
+            verify!(gl::GetTexLevelParameteriv(level_target, 0, gl::TEXTURE_WIDTH, &mut width));
+            verify!(gl::GetTexLevelParameteriv(level_target, 0, gl::TEXTURE_HEIGHT, &mut height));
+        }
+
+        verify!(gl::BindTexture(self.target, 0));
+
+        (width as uint, height as uint)
+    }
+
+    /// The gpu memory used by this texture, in bytes. It is computed from the size and format
+    /// of the texture, and includes its mipmaps, if any. For cube maps, the six faces are counted.
+    pub fn memory_usage(&self) -> uint {
+        let mut width:      GLint = 0;
+        let mut height:     GLint = 0;
+        let mut min_filter: GLint = 0;
+        let mut bits:       GLint = 0;
+        let level_target          = self.level_target();
+
+        verify!(gl::ActiveTexture(gl::TEXTURE0));
+        verify!(gl::BindTexture(self.target, self.id));
+
+        unsafe {
+            verify!(gl::GetTexLevelParameteriv(level_target, 0, gl::TEXTURE_WIDTH, &mut width));
+            verify!(gl::GetTexLevelParameteriv(level_target, 0, gl::TEXTURE_HEIGHT, &mut height));
+            verify!(gl::GetTexParameteriv(self.target, gl::TEXTURE_MIN_FILTER, &mut min_filter));
+
+            for component in [ gl::TEXTURE_RED_SIZE, gl::TEXTURE_GREEN_SIZE,
+                               gl::TEXTURE_BLUE_SIZE, gl::TEXTURE_ALPHA_SIZE ].iter() {
+                let mut component_bits: GLint = 0;
+
+                verify!(gl::GetTexLevelParameteriv(level_target, 0, *component, &mut component_bits));
+
+                bits = bits + component_bits;
+            }
+        }
+
+        verify!(gl::BindTexture(self.target, 0));
+
+        let num_faces = if self.is_cube_map() { 6 } else { 1 };
+        let bytes     = (width * height * bits / 8) as uint * num_faces;
+
+        if min_filter as GLenum == gl::NEAREST || min_filter as GLenum == gl::LINEAR {
+            bytes
+        }
+        else {
+            // the mipmap chain adds a third of the base level size
+            bytes + bytes / 3
+        }
+    }
+
+    /// Changes the sampling parameters of this texture. If `params.mipmaps` is set, the mipmaps
+    /// are generated from the current content of the texture, so this must be called again
+    /// after the texture content is modified.
+    pub fn set_params(&self, params: &TextureParams) {
+        let wrap = match params.wrap {
+            Repeat         => gl::REPEAT,
+            MirroredRepeat => gl::MIRRORED_REPEAT,
+            ClampToEdge    => gl::CLAMP_TO_EDGE
+        };
+
+        let mag_filter = match params.mag_filter {
+            Nearest => gl::NEAREST,
+            Linear  => gl::LINEAR
+        };
+
+        let min_filter = match (params.min_filter, params.mipmaps) {
+            (Nearest, false) => gl::NEAREST,
+            (Linear,  false) => gl::LINEAR,
+            (Nearest, true)  => gl::NEAREST_MIPMAP_NEAREST,
+            (Linear,  true)  => gl::LINEAR_MIPMAP_LINEAR
+        };
+
+        verify!(gl::ActiveTexture(gl::TEXTURE0));
+        verify!(gl::BindTexture(self.target, self.id));
+
+        verify!(gl::TexParameteri(self.target, gl::TEXTURE_WRAP_S, wrap as GLint));
+        verify!(gl::TexParameteri(self.target, gl::TEXTURE_WRAP_T, wrap as GLint));
+
+        if self.is_cube_map() {
+            verify!(gl::TexParameteri(self.target, gl::TEXTURE_WRAP_R, wrap as GLint));
+        }
+
+        verify!(gl::TexParameteri(self.target, gl::TEXTURE_MAG_FILTER, mag_filter as GLint));
+        verify!(gl::TexParameteri(self.target, gl::TEXTURE_MIN_FILTER, min_filter as GLint));
+
+        if params.mipmaps {
+            verify!(gl::GenerateMipmap(self.target));
+        }
+
+        if gl_has_extension("GL_EXT_texture_filter_anisotropic") {
+            let mut max_anisotropy: GLfloat = 1.0;
+
+            unsafe {
+                verify!(gl::GetFloatv(MAX_TEXTURE_MAX_ANISOTROPY_EXT, &mut max_anisotropy));
+            }
+
+            let anisotropy = params.anisotropy.max(&1.0).min(&max_anisotropy);
+
+            verify!(gl::TexParameterf(self.target, TEXTURE_MAX_ANISOTROPY_EXT, anisotropy));
+        }
+    }
 }
 
 impl Drop for Texture {
@@ -39,6 +383,15 @@ impl Drop for Texture {
     }
 }
 
+fn check_pixels_size<T>(width: uint, height: uint, format: PixelFormat, pixels: &[T]) {
+    let expected = width * height * format.pixel_size();
+    let actual   = pixels.len() * mem::size_of::<T>();
+
+    if expected == 0 || expected != actual {
+        fail!("Invalid pixel buffer: expected " + expected.to_str() + " bytes, found " + actual.to_str() + ".");
+    }
+}
+
 local_data_key!(KEY_TEXTURE_MANAGER: @mut TexturesManager)
 
 /// Inits the texture manager, and put in on TLS.
@@ -54,15 +407,22 @@ pub fn singleton() -> @mut TexturesManager {
 }
 
 /// The textures manager. It keeps a cache of already-loaded textures, and can load new textures.
+///
+/// The cache owns a reference to each texture: a texture stays on the gpu until it is removed
+/// from the cache (with `remove` or `release_unused`) and all the objects using it are dropped.
 pub struct TexturesManager {
     priv textures: HashMap<~str, Rc<Texture>>,
+    priv params:   HashMap<~str, TextureParams>, // parameters of the textures read from files
+    priv pinned:   HashSet<~str>                 // textures never released by `release_unused`
 }
 
 impl TexturesManager {
     /// Creates a new texture manager.
     pub fn new() -> TexturesManager {
         TexturesManager {
-            textures: HashMap::new()
+            textures: HashMap::new(),
+            params:   HashMap::new(),
+            pinned:   HashSet::new()
         }
     }
 
@@ -73,41 +433,334 @@ impl TexturesManager {
 
     /// Allocates a new unconfigured texture. If a texture with same name exists, nothing is
     /// created and the old texture is returned.
+    ///
+    /// Textures created this way are never released by `release_unused`: the engine uses them
+    /// for its built-in textures.
     pub fn add_empty(&mut self, name: &str) -> Rc<Texture> {
+        self.pinned.insert(name.to_owned());
         self.textures.find_or_insert_with(name.to_owned(), |_| Texture::new()).clone()
     }
 
-    /// Allocates a new texture read from a file. If a texture with same name exists, nothing is
-    /// created and the old texture is returned.
-    pub fn add(&mut self, path: &str) -> Rc<Texture> {
-        let tex = self.textures.find_or_insert_with(path.to_owned(), |_| Texture::new());
+    /// Allocates a new texture read from a file, with the given sampling parameters. If a 2d
+    /// texture with same name exists, the file is not read again: the old texture is returned,
+    /// with its parameters replaced by `params`. Use `reload` to read the file again. A cube map
+    /// with same name is replaced.
+    ///
+    /// `TextureParams::new()` gives a texture clamped to its edges, linearly filtered and without
+    /// mipmaps.
+    pub fn add(&mut self, path: &str, params: &TextureParams) -> Rc<Texture> {
+        let key = path.to_owned();
 
-        // FIXME: dont re-load the texture if it already exists!
-        unsafe {
+        match self.textures.find(&key) {
+            Some(tex) if !tex.borrow().is_cube_map() => {
+                tex.borrow().set_params(params);
+                self.params.insert(key.clone(), params.clone());
+
+                return tex.clone()
+            },
+            _ => { } // a cube map with same name is replaced
+        }
+
+        let tex = Texture::new();
+
+        load_file(tex.borrow(), path);
+        tex.borrow().set_params(params);
+
+        self.textures.insert(key.clone(), tex.clone());
+        self.params.insert(key, params.clone());
+
+        tex
+    }
+
+    /// Reads again the file of an already loaded texture. The texture keeps the same id and
+    /// parameters, so every object using it displays the new content. Returns `None` if no
+    /// texture was read from `path`, and an error message if the file cannot be read. In this
+    /// case, the texture keeps its previous content.
+    pub fn reload(&mut self, path: &str) -> Option<Result<Rc<Texture>, ~str>> {
+        let key = path.to_owned();
+
+        match (self.textures.find(&key), self.params.find(&key)) {
+            (Some(tex), Some(params)) => {
+                match try_load_file(tex.borrow(), path) {
+                    Ok(_)    => {
+                        tex.borrow().set_params(params);
+
+                        Some(Ok(tex.clone()))
+                    },
+                    Err(err) => Some(Err(err))
+                }
+            },
+            _ => None
+        }
+    }
+
+    /// Removes a texture from the cache. The texture itself is released from the gpu when the
+    /// last object using it is dropped. Returns the removed texture, if any.
+    pub fn remove(&mut self, name: &str) -> Option<Rc<Texture>> {
+        let key = name.to_owned();
+
+        self.params.remove(&key);
+        self.pinned.remove(&key);
+        self.textures.pop(&key)
+    }
+
+    /// Removes from the cache all the textures which are not part of `used`, except the ones
+    /// created with `add_empty` or pinned with `pin`. Returns the number of removed textures.
+    ///
+    /// A texture removed from the cache while still used somewhere else stays on the gpu, and
+    /// adding its file again loads a second copy: `used` must list every texture in use.
+    ///
+    /// # Arguments
+    ///   * `used` - the ids of the textures still in use
+    pub fn release_unused(&mut self, used: &HashSet<GLuint>) -> uint {
+        let mut unused = ~[];
+
+        for (name, tex) in self.textures.iter() {
+            if !self.pinned.contains(name) && !used.contains(&tex.borrow().id()) {
+                unused.push(name.clone());
+            }
+        }
+
+        for name in unused.iter() {
+            self.remove(name.as_slice());
+        }
+
+        unused.len()
+    }
+
+    /// Prevents a texture from being removed from the cache by `release_unused`. This is needed
+    /// for the textures kept outside of the scene, e.g. to be applied to objects later.
+    pub fn pin(&mut self, name: &str) {
+        self.pinned.insert(name.to_owned());
+    }
+
+    /// Allows a texture pinned with `pin` to be removed from the cache by `release_unused` again.
+    pub fn unpin(&mut self, name: &str) {
+        self.pinned.remove(&name.to_owned());
+    }
+
+    /// The paths of the textures of the cache read from files.
+    pub fn file_paths(&self) -> ~[~str] {
+        self.params.keys().map(|p| p.clone()).collect()
+    }
+
+    /// The number of textures in the cache.
+    pub fn num_textures(&self) -> uint {
+        self.textures.len()
+    }
+
+    /// The gpu memory used by the textures of the cache, in bytes.
+    pub fn memory_usage(&self) -> uint {
+        self.textures.iter().fold(0, |total, (_, tex)| total + tex.borrow().memory_usage())
+    }
+
+    // The texture named `name` if it has the right kind, or a new texture replacing it.
+    fn find_or_replace(&mut self, name: &str, cube_map: bool) -> Rc<Texture> {
+        let key = name.to_owned();
+
+        match self.textures.find(&key) {
+            Some(tex) if tex.borrow().is_cube_map() == cube_map => return tex.clone(),
+            _ => { }
+        }
+
+        let tex = if cube_map { Texture::new_cube_map() } else { Texture::new() };
+
+        // the replaced texture is not read from a file anymore
+        self.params.remove(&key);
+        self.textures.insert(key, tex.clone());
+
+        tex
+    }
+
+    /// Allocates a new cube map from six square images of the same size. If a cube map with same
+    /// name exists, its content is replaced. A 2d texture with same name is replaced. Cube maps are used by skyboxes and environment
+    /// reflections.
+    ///
+    /// # Arguments
+    ///   * `name` - the name of the cube map, used to retrieve it with `get`
+    ///   * `faces` - relative paths of the images of the faces on the disk, in the order `+x, -x,
+    ///   +y, -y, +z, -z`
+    pub fn add_cube_map(&mut self, name: &str, faces: &[&str]) -> Rc<Texture> {
+        assert!(faces.len() == 6, "A cube map must have exactly six faces.");
+
+        let tex = self.find_or_replace(name, true);
+
+        bind_cube_map(tex.borrow());
+
+        for (i, path) in faces.iter().enumerate() {
             match image::load_with_depth(path.to_owned(), 3, false) {
                 ImageU8(image) => {
-                    verify!(gl::ActiveTexture(gl::TEXTURE0));
-                    verify!(gl::BindTexture(gl::TEXTURE_2D, tex.borrow().id()));
-
-                    verify!(gl::TexImage2D(
-                            gl::TEXTURE_2D, 0,
-                            gl::RGB as GLint,
-                            image.width as GLsizei,
-                            image.height as GLsizei,
-                            0, gl::RGB, gl::UNSIGNED_BYTE,
-                            cast::transmute(&image.data[0])));
-
-                    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint));
-                    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint));
-                    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint));
-                    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint));
+                    if image.width != image.height {
+                        fail!("The cube map faces must be square: " + *path);
+                    }
+
+                    upload_cube_face(i, image.width, image.data);
                 }
-                _ => {
-                    fail!("Failed to load texture " + path);
+                _ => fail!("Failed to load texture " + *path)
+            }
+        }
+
+        set_cube_map_params();
+
+        tex
+    }
+
+    /// Allocates a new cube map from an equirectangular panorama, i-e. an image with a 2:1 ratio
+    /// covering 360 degrees horizontally and 180 degrees vertically. If a cube map with same name
+    /// exists, its content is replaced. A 2d texture with same name is replaced.
+    ///
+    /// # Arguments
+    ///   * `name` - the name of the cube map, used to retrieve it with `get`
+    ///   * `path` - relative path of the panorama on the disk. Its center is mapped to the `-z`
+    ///   direction.
+    ///   * `face_size` - the width and height of each face of the cube map, in pixels
+    pub fn add_cube_map_from_panorama(&mut self, name: &str, path: &str, face_size: uint) -> Rc<Texture> {
+        let tex = self.find_or_replace(name, true);
+
+        bind_cube_map(tex.borrow());
+
+        match image::load_with_depth(path.to_owned(), 3, false) {
+            ImageU8(image) => {
+                for face in range(0u, 6) {
+                    let pixels = panorama_face(image.data, image.width, image.height, face, face_size);
+
+                    upload_cube_face(face, face_size, pixels);
                 }
             }
+            _ => fail!("Failed to load texture " + path)
         }
 
-        tex.clone()
+        set_cube_map_params();
+
+        tex
+    }
+
+    /// Allocates a new texture from a buffer of 8-bits RGBA pixels. If a texture with same name
+    /// exists, its content is replaced.
+    ///
+    /// # Arguments
+    ///   * `name` - the name of the texture, used to retrieve it with `get`
+    ///   * `width` - the width of the texture, in pixels
+    ///   * `height` - the height of the texture, in pixels
+    ///   * `pixels` - the `4 * width * height` pixel components, row by row, starting with the
+    ///   bottom row
+    pub fn add_from_rgba(&mut self, name: &str, width: uint, height: uint, pixels: &[u8]) -> Rc<Texture> {
+        self.add_from_pixels(name, width, height, Rgba8, pixels)
+    }
+
+    /// Allocates a new texture from a buffer of pixels with the given format. If a 2d texture
+    /// with same name exists, its content is replaced. A cube map with same name is replaced. See
+    /// `Texture::upload` for the layout of `pixels`.
+    ///
+    /// The texture is clamped to its edges, linearly filtered and has no mipmaps. Use
+    /// `Texture::set_params` to change this.
+    pub fn add_from_pixels<T>(&mut self,
+                              name:   &str,
+                              width:  uint,
+                              height: uint,
+                              format: PixelFormat,
+                              pixels: &[T]) -> Rc<Texture> {
+        let tex = self.find_or_replace(name, false);
+
+        tex.borrow().upload(width, height, format, pixels);
+        tex.borrow().set_params(&TextureParams::new());
+
+        tex
+    }
+}
+
+// Sets the parameters of the cube map bound to `TEXTURE_CUBE_MAP`.
+fn set_cube_map_params() {
+    verify!(gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint));
+    verify!(gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint));
+    verify!(gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_WRAP_R, gl::CLAMP_TO_EDGE as GLint));
+    verify!(gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint));
+    verify!(gl::TexParameteri(gl::TEXTURE_CUBE_MAP, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint));
+
+    // avoid visible seams between the faces
+    if gl_version_at_least(3, 2) || gl_has_extension("GL_ARB_seamless_cube_map") {
+        verify!(gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS));
+    }
+}
+
+// Uploads one face of the cube map bound to `TEXTURE_CUBE_MAP`. `face` is in the order
+// `+x, -x, +y, -y, +z, -z`.
+fn upload_cube_face(face: uint, size: uint, pixels: &[u8]) {
+    check_pixels_size(size, size, Rgb8, pixels);
+
+    unsafe {
+        verify!(gl::TexImage2D(gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as GLenum, 0,
+                               gl::RGB8 as GLint,
+                               size as GLsizei,
+                               size as GLsizei,
+                               0, gl::RGB, gl::UNSIGNED_BYTE,
+                               cast::transmute(&pixels[0])));
+    }
+}
+
+fn bind_cube_map(tex: &Texture) {
+    verify!(gl::ActiveTexture(gl::TEXTURE0));
+    verify!(gl::BindTexture(gl::TEXTURE_CUBE_MAP, tex.id()));
+    verify!(gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1));
+}
+
+// Direction of a point of a cube map face, following the opengl cube map conventions. `u` and `v`
+// are the face coordinates of the point, in `[-1.0, 1.0]`.
+fn cube_face_direction(face: uint, u: f32, v: f32) -> Vec3<f32> {
+    match face {
+        0 => Vec3::new(1.0, -v, -u),
+        1 => Vec3::new(-1.0, -v, u),
+        2 => Vec3::new(u, 1.0, v),
+        3 => Vec3::new(u, -1.0, -v),
+        4 => Vec3::new(u, -v, 1.0),
+        _ => Vec3::new(-u, -v, -1.0)
+    }
+}
+
+// Resamples an equirectangular panorama into one face of a cube map. The panorama center looks
+// toward `-z`, its top toward `+y`.
+fn panorama_face(panorama: &[u8], width: uint, height: uint, face: uint, size: uint) -> ~[u8] {
+    let pi: f32    = Real::pi();
+    let mut pixels = vec::with_capacity(size * size * 3);
+
+    for j in range(0u, size) {
+        for i in range(0u, size) {
+            let u   = 2.0 * (i as f32 + 0.5) / (size as f32) - 1.0;
+            let v   = 2.0 * (j as f32 + 0.5) / (size as f32) - 1.0;
+            let dir = na::normalize(&cube_face_direction(face, u, v));
+
+            let longitude = atan2(dir.x, -dir.z);
+            let latitude  = dir.y.asin();
+
+            let px = ((0.5 + longitude / (2.0 * pi)) * (width as f32)) as uint;
+            let py = ((0.5 - latitude / pi) * (height as f32)) as uint;
+            let id = (py.min(&(height - 1)) * width + px.min(&(width - 1))) * 3;
+
+            pixels.push(panorama[id]);
+            pixels.push(panorama[id + 1]);
+            pixels.push(panorama[id + 2]);
+        }
+    }
+
+    pixels
+}
+
+fn load_file(tex: &Texture, path: &str) {
+    match try_load_file(tex, path) {
+        Ok(_)    => { },
+        Err(err) => fail!(err)
+    }
+}
+
+// Same as `load_file` but returns an error message instead of failing. The texture is left
+// untouched on error.
+fn try_load_file(tex: &Texture, path: &str) -> Result<(), ~str> {
+    match image::load_with_depth(path.to_owned(), 3, false) {
+        ImageU8(image) => {
+            tex.upload(image.width, image.height, Rgb8, image.data);
+
+            Ok(())
+        },
+        _ => Err("Failed to load texture " + path)
     }
 }
@@ -4,20 +4,27 @@ pub static OBJECT_VERTEX_SRC: &'static str =
     attribute vec3 normal;
     attribute vec3 color;
     attribute vec2 tex_coord_v;
+    attribute vec4 tangent;
     varying vec3 ws_normal;
+    varying vec3 ws_tangent;
+    varying float tangent_w;
     varying vec3 ws_position;
     varying vec2 tex_coord;
     uniform mat4 view;
     uniform mat4 transform;
     uniform mat3 scale;
     uniform mat3 ntransform;
+    uniform vec2 uv_scale;
+    uniform vec2 uv_offset;
     void main() {
         mat4 scale4 = mat4(scale);
         vec4 pos4   = transform * scale4 * vec4(position, 1.0);
-        tex_coord   = tex_coord_v;
+        tex_coord   = tex_coord_v * uv_scale + uv_offset;
         ws_position = pos4.xyz;
         gl_Position = view * transform * scale4 * vec4(position, 1.0);
         ws_normal   = normalize(ntransform * scale * normal);
+        ws_tangent  = normalize(ntransform * scale * tangent.xyz);
+        tangent_w   = tangent.w;
     }";
 
 // phong lighting (heavily) inspired
@@ -26,27 +33,69 @@ pub static OBJECT_FRAGMENT_SRC: &'static str =
    "#version 120
     uniform vec3      color;
     uniform vec3      light_position;
-    uniform sampler2D tex;
+    uniform vec3      eye_position;
+    uniform sampler2D diffuse_map;
+    uniform sampler2D normal_map;
+    uniform sampler2D specular_map;
+    uniform sampler2D emissive_map;
+    uniform bool      flat_shading;
+    uniform bool      use_normal_map;
+    uniform samplerCube environment_map;
+    uniform bool      use_environment_map;
+    uniform float     reflectivity;
     varying vec2      tex_coord;
     varying vec3      ws_normal;
+    varying vec3      ws_tangent;
+    varying float     tangent_w;
     varying vec3      ws_position;
     void main() {
       vec3 L = normalize(light_position - ws_position);
-      vec3 E = normalize(-ws_position);
+      vec3 E = normalize(eye_position - ws_position);
+      vec3 N = normalize(ws_normal);
+
+      // the face normal is orthogonal to the screen-space derivatives of the position. Its sign
+      // does not matter since the lighting is double sided.
+      if (flat_shading) {
+        N = normalize(cross(dFdx(ws_position), dFdy(ws_position)));
+      }
+
+      // the normal map gives the normal in tangent space
+      if (use_normal_map) {
+        vec3 T  = normalize(ws_tangent - N * dot(N, ws_tangent));
+        vec3 B  = cross(N, T) * tangent_w;
+        vec3 tn = texture2D(normal_map, tex_coord).xyz * 2.0 - 1.0;
+        N       = normalize(T * tn.x + B * tn.y + N * tn.z);
+      }
 
       //calculate Ambient Term:
       vec4 Iamb = vec4(1.0, 1.0, 1.0, 1.0);
 
       //calculate Diffuse Term:
-      vec4 Idiff1 = vec4(1.0, 1.0, 1.0, 1.0) * max(dot(ws_normal,L), 0.0);
+      vec4 Idiff1 = vec4(1.0, 1.0, 1.0, 1.0) * max(dot(N,L), 0.0);
       Idiff1 = clamp(Idiff1, 0.0, 1.0);
 
       // double sided lighting:
-      vec4 Idiff2 = vec4(1.0, 1.0, 1.0, 1.0) * max(dot(-ws_normal,L), 0.0);
+      vec4 Idiff2 = vec4(1.0, 1.0, 1.0, 1.0) * max(dot(-N,L), 0.0);
       Idiff2 = clamp(Idiff2, 0.0, 1.0);
 
-      vec4 tex_color = texture2D(tex, tex_coord);
-      gl_FragColor   = tex_color * (vec4(color, 1.0) + Iamb + (Idiff1 + Idiff2) / 2) / 3;
+      // specular term, with the normal facing the viewer. The default specular map is black.
+      vec3 Nv     = dot(N, E) < 0.0 ? -N : N;
+      vec3 R      = reflect(-L, Nv);
+      vec3 Ispec  = texture2D(specular_map, tex_coord).rgb * pow(max(dot(R, E), 0.0), 32.0);
+
+      // emissive term. The default emissive map is black.
+      vec3 Iemiss = texture2D(emissive_map, tex_coord).rgb;
+
+      vec4 tex_color = texture2D(diffuse_map, tex_coord);
+      vec4 lit_color = tex_color * (vec4(color, 1.0) + Iamb + (Idiff1 + Idiff2) / 2) / 3;
+
+      // reflection of the environment, in world space like the cube map
+      if (use_environment_map && reflectivity > 0.0) {
+        vec4 env  = textureCube(environment_map, reflect(-E, Nv));
+        lit_color = mix(lit_color, vec4(env.rgb, lit_color.a), reflectivity);
+      }
+
+      gl_FragColor = lit_color + vec4(Ispec + Iemiss, 0.0);
     }";
 
 pub static LINES_VERTEX_SRC: &'static str =
@@ -68,3 +117,196 @@ pub static LINES_FRAGMENT_SRC: &'static str =
     void main() {
       gl_FragColor = vec4(Color, 1.0);
     }";
+
+// Each segment is drawn as a quad. Its vertices are pushed away from the segment along its
+// screen-space normal so that the line has a constant width in pixels.
+pub static POLYLINE_VERTEX_SRC: &'static str =
+   "#version 120
+    attribute vec3  position;
+    attribute vec3  other;
+    attribute float side;
+    attribute float dist;
+    varying   float line_dist;
+    uniform   mat4  view;
+    uniform   vec2  viewport;
+    uniform   float width;
+    void main() {
+        vec4 clip_pos   = view * vec4(position, 1.0);
+        vec4 clip_other = view * vec4(other, 1.0);
+        vec2 dir        = clip_other.xy / clip_other.w * viewport - clip_pos.xy / clip_pos.w * viewport;
+
+        if (length(dir) < 0.00001) {
+            dir = vec2(1.0, 0.0);
+        }
+
+        vec2 normal = normalize(vec2(-dir.y, dir.x));
+        vec2 offset = normal * side * width / viewport;
+
+        gl_Position = vec4(clip_pos.xy + offset * clip_pos.w, clip_pos.zw);
+        line_dist   = dist;
+    }";
+
+pub static POLYLINE_FRAGMENT_SRC: &'static str =
+   "#version 120
+    uniform vec3  color;
+    uniform vec2  dash;
+    varying float line_dist;
+    void main() {
+      if (dash.y > 0.0 && mod(line_dist, dash.x + dash.y) > dash.x) {
+          discard;
+      }
+
+      gl_FragColor = vec4(color, 1.0);
+    }";
+
+// Overlay primitives are given in pixels, with the origin at the top-left corner of the window.
+pub static OVERLAY_VERTEX_SRC: &'static str =
+   "#version 120
+    attribute vec2 position;
+    attribute vec3 color;
+    varying   vec3 Color;
+    uniform   vec2 viewport;
+    void main() {
+        gl_Position = vec4(2.0 * position.x / viewport.x - 1.0, 1.0 - 2.0 * position.y / viewport.y, 0.0, 1.0);
+        Color = color;
+    }";
+
+pub static OVERLAY_FRAGMENT_SRC: &'static str =
+   "#version 120
+    varying vec3 Color;
+    void main() {
+      gl_FragColor = vec4(Color, 1.0);
+    }";
+
+// Text is given in pixels, with the origin at the top-left corner of the window. The glyphs are
+// read from the alpha channel of the font atlas.
+pub static TEXT_VERTEX_SRC: &'static str =
+   "#version 120
+    attribute vec2 position;
+    attribute vec2 tex_coord_v;
+    attribute vec3 color;
+    varying   vec2 tex_coord;
+    varying   vec3 Color;
+    uniform   vec2 viewport;
+    void main() {
+        gl_Position = vec4(2.0 * position.x / viewport.x - 1.0, 1.0 - 2.0 * position.y / viewport.y, 0.0, 1.0);
+        tex_coord   = tex_coord_v;
+        Color       = color;
+    }";
+
+pub static TEXT_FRAGMENT_SRC: &'static str =
+   "#version 120
+    uniform sampler2D font;
+    varying vec2      tex_coord;
+    varying vec3      Color;
+    void main() {
+      if (texture2D(font, tex_coord).a < 0.5) {
+          discard;
+      }
+
+      gl_FragColor = vec4(Color, 1.0);
+    }";
+
+// Same as the object shader, except that the transformation and the color are per-instance
+// vertex attributes.
+pub static INSTANCED_OBJECT_VERTEX_SRC: &'static str =
+   "#version 120
+    attribute vec3 position;
+    attribute vec3 normal;
+    attribute vec2 tex_coord_v;
+    attribute mat4 inst_transform;
+    attribute vec3 inst_color;
+    varying vec3 ws_normal;
+    varying vec3 ws_position;
+    varying vec2 tex_coord;
+    varying vec3 Color;
+    uniform mat4 view;
+    uniform mat3 scale;
+    void main() {
+        mat4 scale4 = mat4(scale);
+        vec4 pos4   = inst_transform * scale4 * vec4(position, 1.0);
+        tex_coord   = tex_coord_v;
+        ws_position = pos4.xyz;
+        gl_Position = view * pos4;
+        ws_normal   = normalize(mat3(inst_transform) * scale * normal);
+        Color       = inst_color;
+    }";
+
+pub static INSTANCED_OBJECT_FRAGMENT_SRC: &'static str =
+   "#version 120
+    uniform vec3      light_position;
+    uniform sampler2D tex;
+    varying vec2      tex_coord;
+    varying vec3      ws_normal;
+    varying vec3      ws_position;
+    varying vec3      Color;
+    void main() {
+      vec3 L = normalize(light_position - ws_position);
+
+      //calculate Ambient Term:
+      vec4 Iamb = vec4(1.0, 1.0, 1.0, 1.0);
+
+      //calculate Diffuse Term:
+      vec4 Idiff1 = vec4(1.0, 1.0, 1.0, 1.0) * max(dot(ws_normal,L), 0.0);
+      Idiff1 = clamp(Idiff1, 0.0, 1.0);
+
+      // double sided lighting:
+      vec4 Idiff2 = vec4(1.0, 1.0, 1.0, 1.0) * max(dot(-ws_normal,L), 0.0);
+      Idiff2 = clamp(Idiff2, 0.0, 1.0);
+
+      vec4 tex_color = texture2D(tex, tex_coord);
+      gl_FragColor   = tex_color * (vec4(Color, 1.0) + Iamb + (Idiff1 + Idiff2) / 2) / 3;
+    }";
+
+pub static SKYBOX_VERTEX_SRC: &'static str =
+   "#version 120
+    attribute vec2 position;
+    varying   vec2 ndc_position;
+    void main() {
+        ndc_position = position;
+        gl_Position  = vec4(position, 0.0, 1.0);
+    }";
+
+// the view direction is computed by unprojecting the fragment on the near plane.
+pub static SKYBOX_FRAGMENT_SRC: &'static str =
+   "#version 120
+    uniform mat4        inv_proj_view;
+    uniform vec3        eye_position;
+    uniform samplerCube skybox;
+    varying vec2        ndc_position;
+    void main() {
+        vec4 p       = inv_proj_view * vec4(ndc_position, -1.0, 1.0);
+        gl_FragColor = textureCube(skybox, p.xyz / p.w - eye_position);
+    }";
+
+pub static BACKGROUND_VERTEX_SRC: &'static str =
+   "#version 120
+    attribute vec2 position;
+    varying   vec2 uv;
+    void main() {
+        uv          = position * 0.5 + 0.5;
+        gl_Position = vec4(position, 0.0, 1.0);
+    }";
+
+// `mode` is 0 for a vertical gradient, 1 for a radial gradient, and 2 for an image.
+pub static BACKGROUND_FRAGMENT_SRC: &'static str =
+   "#version 120
+    uniform int       mode;
+    uniform vec3      color1;
+    uniform vec3      color2;
+    uniform sampler2D tex;
+    uniform vec2      tiling;
+    varying vec2      uv;
+    void main() {
+        if (mode == 0) {
+            gl_FragColor = vec4(mix(color1, color2, uv.y), 1.0);
+        }
+        else if (mode == 1) {
+            // the corners are at a distance of 1.0 from the center
+            float d      = min(length(uv - 0.5) * sqrt(2.0), 1.0);
+            gl_FragColor = vec4(mix(color1, color2, d), 1.0);
+        }
+        else {
+            gl_FragColor = vec4(texture2D(tex, uv * tiling).rgb, 1.0);
+        }
+    }";
@@ -0,0 +1,86 @@
+//! A cube map drawn behind the scene.
+
+use std::ptr;
+use std::cast;
+use std::mem;
+use std::rc::Rc;
+use gl;
+use gl::types::*;
+use nalgebra::na::{Vec3, Mat4};
+use resources::shaders_manager::SkyboxShaderContext;
+use resources::textures_manager::Texture;
+
+#[path = "error.rs"]
+mod error;
+
+/// A skybox. It covers the whole screen and is drawn before the scene, without depth.
+pub struct Skybox {
+    priv texture: Rc<Texture>,
+    priv vbuf:    GLuint // screen-covering quad
+}
+
+impl Skybox {
+    /// Creates a skybox displaying a cube map.
+    pub fn new(cube_map: Rc<Texture>) -> Skybox {
+        let quad: [GLfloat, ..8] = [ -1.0, -1.0,
+                                      1.0, -1.0,
+                                     -1.0,  1.0,
+                                      1.0,  1.0 ];
+        let mut vbuf: GLuint = 0;
+
+        unsafe {
+            verify!(gl::GenBuffers(1, &mut vbuf));
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, vbuf));
+            verify!(gl::BufferData(gl::ARRAY_BUFFER,
+                                   (quad.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                                   cast::transmute(&quad[0]),
+                                   gl::STATIC_DRAW));
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, 0));
+        }
+
+        Skybox {
+            texture: cube_map,
+            vbuf:    vbuf
+        }
+    }
+
+    /// The cube map displayed by this skybox.
+    pub fn texture<'r>(&'r self) -> &'r Rc<Texture> {
+        &'r self.texture
+    }
+
+    /// Draws the skybox. The skybox shader must be selected.
+    ///
+    /// # Arguments
+    ///   * `inv_proj_view` - the inverse of the camera projection and view transformation
+    ///   * `eye` - the camera position
+    pub fn upload(&self, context: &SkyboxShaderContext, inv_proj_view: &Mat4<f32>, eye: &Vec3<f32>) {
+        unsafe {
+            verify!(gl::UniformMatrix4fv(context.inv_proj_view, 1, gl::FALSE as u8, cast::transmute(inv_proj_view)));
+        }
+        verify!(gl::Uniform3f(context.eye, eye.x, eye.y, eye.z));
+
+        verify!(gl::ActiveTexture(gl::TEXTURE0));
+        verify!(gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.texture.borrow().id()));
+
+        verify!(gl::DepthMask(gl::FALSE));
+        verify!(gl::Disable(gl::DEPTH_TEST));
+
+        unsafe {
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, self.vbuf));
+            verify!(gl::VertexAttribPointer(context.pos, 2, gl::FLOAT, gl::FALSE as u8, 0, ptr::null()));
+        }
+        verify!(gl::DrawArrays(gl::TRIANGLE_STRIP, 0, 4));
+        verify!(gl::BindBuffer(gl::ARRAY_BUFFER, 0));
+
+        verify!(gl::Enable(gl::DEPTH_TEST));
+        verify!(gl::DepthMask(gl::TRUE));
+        verify!(gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0));
+    }
+}
+
+impl Drop for Skybox {
+    fn drop(&mut self) {
+        unsafe { verify!(gl::DeleteBuffers(1, &self.vbuf)); }
+    }
+}
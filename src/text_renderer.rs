@@ -0,0 +1,199 @@
+use std::ptr;
+use std::cast;
+use std::mem;
+use std::vec;
+use std::rc::Rc;
+use gl;
+use gl::types::*;
+use nalgebra::na::{Vec2, Vec3};
+use resources::shaders_manager::TextShaderContext;
+use resources::textures_manager;
+use resources::textures_manager::Texture;
+use builtins::font8x8;
+
+#[path = "error.rs"]
+mod error;
+
+/// Number of floats per vertex: position (2), texture coordinates (2) and color (3).
+static VERTEX_SIZE: uint = 7;
+/// Number of glyphs per line of the font atlas.
+static ATLAS_COLUMNS: uint = 16;
+/// Number of lines of glyphs of the font atlas.
+static ATLAS_ROWS: uint = 6;
+
+/// Structure which manages the display of short-living text drawn on top of the scene. The
+/// glyphs are read from a builtin bitmap font packed on a texture atlas.
+struct TextRenderer {
+    priv atlas:    Rc<Texture>,
+    priv vertices: ~[GLfloat],
+    priv vbuf:     GLuint,
+    priv max_len:  uint
+}
+
+impl TextRenderer {
+    /// Creates a new text renderer. This uploads the font atlas to the gpu.
+    pub fn new() -> TextRenderer {
+        let mut vbuf: GLuint = 0;
+
+        unsafe { verify!(gl::GenBuffers(1, &mut vbuf)) };
+
+        TextRenderer {
+            atlas:    load_font_atlas(),
+            vertices: ~[],
+            vbuf:     vbuf,
+            max_len:  0
+        }
+    }
+
+    /// Indicates whether some text has to be drawn.
+    pub fn needs_rendering(&self) -> bool {
+        self.vertices.len() != 0
+    }
+
+    /// Adds some text to be drawn during the next frame.
+    ///
+    /// # Arguments
+    ///   * `text` - the text to draw. Non-ascii characters are displayed as `?`
+    ///   * `pos` - the top-left corner of the text, in pixels
+    ///   * `size` - the height of one line of text, in pixels
+    ///   * `color` - the text color
+    pub fn draw_text(&mut self, text: &str, pos: &Vec2<GLfloat>, size: GLfloat, color: &Vec3<GLfloat>) {
+        let mut x = pos.x;
+        let mut y = pos.y;
+
+        for c in text.iter() {
+            if c == '\n' {
+                x = pos.x;
+                y = y + size;
+                continue
+            }
+
+            let glyph = if c as uint >= font8x8::FIRST_CHAR as uint && c as uint <= font8x8::LAST_CHAR as uint {
+                c as uint - font8x8::FIRST_CHAR as uint
+            }
+            else {
+                '?' as uint - font8x8::FIRST_CHAR as uint
+            };
+
+            let u0 = (glyph % ATLAS_COLUMNS) as GLfloat / (ATLAS_COLUMNS as GLfloat);
+            let v0 = (glyph / ATLAS_COLUMNS) as GLfloat / (ATLAS_ROWS as GLfloat);
+            let u1 = u0 + 1.0 / (ATLAS_COLUMNS as GLfloat);
+            let v1 = v0 + 1.0 / (ATLAS_ROWS as GLfloat);
+
+            push_vertex(&mut self.vertices, x, y, u0, v0, color);
+            push_vertex(&mut self.vertices, x, y + size, u0, v1, color);
+            push_vertex(&mut self.vertices, x + size, y + size, u1, v1, color);
+
+            push_vertex(&mut self.vertices, x, y, u0, v0, color);
+            push_vertex(&mut self.vertices, x + size, y + size, u1, v1, color);
+            push_vertex(&mut self.vertices, x + size, y, u1, v0, color);
+
+            x = x + size;
+        }
+    }
+
+    /// Actually draws the text. It is drawn on top of everything else.
+    pub fn upload(&mut self, context: &TextShaderContext, width: f32, height: f32) {
+        if !self.needs_rendering() { return }
+
+        verify!(gl::Disable(gl::DEPTH_TEST));
+        verify!(gl::Uniform2f(context.viewport, width, height));
+
+        verify!(gl::ActiveTexture(gl::TEXTURE0));
+        verify!(gl::BindTexture(gl::TEXTURE_2D, self.atlas.borrow().id()));
+        verify!(gl::Uniform1i(context.font, 0));
+
+        unsafe {
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, self.vbuf));
+
+            if self.vertices.len() > self.max_len {
+                // realloc the vertex buffer
+                self.max_len = self.vertices.capacity();
+
+                verify!(gl::BufferData(
+                    gl::ARRAY_BUFFER,
+                    (self.max_len * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                    ptr::null(),
+                    gl::STREAM_DRAW));
+            }
+
+            verify!(gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                0,
+                (self.vertices.len() * mem::size_of::<GLfloat>()) as GLsizeiptr,
+                cast::transmute(&self.vertices[0])));
+
+            let stride = (VERTEX_SIZE * mem::size_of::<GLfloat>()) as GLint;
+
+            verify!(gl::VertexAttribPointer(context.pos, 2, gl::FLOAT, gl::FALSE as u8, stride, ptr::null()));
+            verify!(gl::VertexAttribPointer(context.tex_coord, 2, gl::FLOAT, gl::FALSE as u8, stride,
+                                            cast::transmute(2 * mem::size_of::<GLfloat>())));
+            verify!(gl::VertexAttribPointer(context.color, 3, gl::FLOAT, gl::FALSE as u8, stride,
+                                            cast::transmute(4 * mem::size_of::<GLfloat>())));
+
+            verify!(gl::DrawArrays(gl::TRIANGLES, 0, (self.vertices.len() / VERTEX_SIZE) as GLint));
+
+            verify!(gl::BindBuffer(gl::ARRAY_BUFFER, 0));
+        }
+
+        verify!(gl::Enable(gl::DEPTH_TEST));
+
+        self.vertices.clear();
+    }
+}
+
+fn push_vertex(out: &mut ~[GLfloat], x: GLfloat, y: GLfloat, u: GLfloat, v: GLfloat, color: &Vec3<GLfloat>) {
+    out.push(x);
+    out.push(y);
+    out.push(u);
+    out.push(v);
+    out.push(color.x);
+    out.push(color.y);
+    out.push(color.z);
+}
+
+// Rasterises the builtin bitmap font on a single-channel texture. The first line of pixels of the
+// image corresponds to the `v = 0` texture coordinate.
+fn load_font_atlas() -> Rc<Texture> {
+    let gsz    = font8x8::GLYPH_SIZE;
+    let width  = ATLAS_COLUMNS * gsz;
+    let height = ATLAS_ROWS * gsz;
+    let mut pixels = vec::from_elem(width * height, 0u8);
+
+    for (g, glyph) in font8x8::FONT8X8.iter().enumerate() {
+        let x0 = (g % ATLAS_COLUMNS) * gsz;
+        let y0 = (g / ATLAS_COLUMNS) * gsz;
+
+        for (y, row) in glyph.iter().enumerate() {
+            for x in range(0u, gsz) {
+                if (*row >> x) & 1 != 0 {
+                    pixels[(y0 + y) * width + x0 + x] = 255;
+                }
+            }
+        }
+    }
+
+    let tex = textures_manager::singleton().add_empty("font8x8");
+
+    verify!(gl::ActiveTexture(gl::TEXTURE0));
+    verify!(gl::BindTexture(gl::TEXTURE_2D, tex.borrow().id()));
+    verify!(gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1));
+    unsafe {
+        verify!(gl::TexImage2D(gl::TEXTURE_2D, 0, gl::ALPHA as GLint, width as GLsizei, height as GLsizei,
+                               0, gl::ALPHA, gl::UNSIGNED_BYTE, cast::transmute(&pixels[0])));
+    }
+    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as GLint));
+    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as GLint));
+    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as GLint));
+    verify!(gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as GLint));
+    verify!(gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4));
+    verify!(gl::BindTexture(gl::TEXTURE_2D, 0));
+
+    tex
+}
+
+impl Drop for TextRenderer {
+    fn drop(&mut self) {
+        unsafe { verify!(gl::DeleteBuffers(1, &self.vbuf)); }
+    }
+}
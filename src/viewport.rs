@@ -0,0 +1,154 @@
+//! Sub-rectangles of the window, each showing the scene from its own camera.
+
+use std::borrow;
+use std::rc::RcMut;
+use camera::Camera;
+use background::Background;
+use object::ALL_LAYERS;
+
+/// Set of datas identifying a viewport.
+pub struct ViewportData {
+    priv camera:     @mut Camera,
+    priv x:          f32, // the rectangle is expressed in fractions of the window size
+    priv y:          f32,
+    priv width:      f32,
+    priv height:     f32,
+    priv background: Option<Background>,
+    priv layers:     u32,
+    priv size:       (f32, f32) // last pixel size sent to the camera
+}
+
+/// A rectangular part of the window displaying the scene seen from its own camera. Viewports
+/// make it possible to show, e.g., the top, front, side and perspective views of a CAD quad
+/// view side by side.
+///
+/// The rectangle is given in fractions of the window size, with the origin at the top-left
+/// corner of the window: it follows the window when it is resized.
+#[deriving(Clone)]
+pub struct Viewport {
+    priv data: RcMut<ViewportData>
+}
+
+impl Viewport {
+    #[doc(hidden)]
+    pub fn new(camera: @mut Camera, x: f32, y: f32, width: f32, height: f32) -> Viewport {
+        let data = ViewportData {
+            camera:     camera,
+            x:          x,
+            y:          y,
+            width:      width,
+            height:     height,
+            background: None,
+            layers:     ALL_LAYERS,
+            size:       (0.0, 0.0)
+        };
+
+        Viewport {
+            data: RcMut::new(data)
+        }
+    }
+
+    /// The camera of this viewport.
+    pub fn camera(&self) -> @mut Camera {
+        self.data.with_borrow(|d| d.camera)
+    }
+
+    /// Sets the camera of this viewport.
+    pub fn set_camera(&mut self, camera: @mut Camera) {
+        do self.data.with_mut_borrow |d| {
+            d.camera = camera;
+            // force the new camera to be notified of the viewport size
+            d.size   = (0.0, 0.0);
+        }
+    }
+
+    /// The rectangle `(x, y, width, height)` covered by this viewport, in fractions of the window
+    /// size.
+    pub fn rect(&self) -> (f32, f32, f32, f32) {
+        self.data.with_borrow(|d| (d.x, d.y, d.width, d.height))
+    }
+
+    /// Sets the rectangle covered by this viewport, in fractions of the window size. The origin
+    /// is at the top-left corner of the window.
+    pub fn set_rect(&mut self, x: f32, y: f32, width: f32, height: f32) {
+        do self.data.with_mut_borrow |d| {
+            d.x      = x;
+            d.y      = y;
+            d.width  = width;
+            d.height = height;
+        }
+    }
+
+    /// The background drawn behind this viewport. `None` means the window background is used.
+    pub fn background(&self) -> Option<Background> {
+        self.data.with_borrow(|d| d.background.clone())
+    }
+
+    /// Sets the background drawn behind this viewport. `None` means the window background is
+    /// used. Fails if the image of an `Image` background is a cube map.
+    pub fn set_background(&mut self, background: Option<Background>) {
+        for b in background.iter() {
+            b.check();
+        }
+
+        self.data.with_mut_borrow(|d| d.background = background)
+    }
+
+    /// The mask of the object layers displayed by this viewport.
+    pub fn layers(&self) -> u32 {
+        self.data.with_borrow(|d| d.layers)
+    }
+
+    /// Sets the mask of the object layers displayed by this viewport. An object is drawn only if
+    /// it belongs to at least one layer of the mask (see `Object::set_layers`). The default is
+    /// `object::ALL_LAYERS`.
+    pub fn set_layers(&mut self, layers: u32) {
+        self.data.with_mut_borrow(|d| d.layers = layers)
+    }
+
+    /// Converts the viewport rectangle to pixels, with the origin at the bottom-left corner of
+    /// the window, as expected by `gl::Viewport`.
+    #[doc(hidden)]
+    pub fn pixel_rect(&self, window_width: f32, window_height: f32) -> (i32, i32, i32, i32) {
+        do self.data.with_borrow |d| {
+            let x = (d.x * window_width) as i32;
+            let w = (d.width * window_width) as i32;
+            let h = (d.height * window_height) as i32;
+            let y = ((1.0 - d.y - d.height) * window_height) as i32;
+
+            (x, y, w, h)
+        }
+    }
+
+    /// Returns true if the given point, in pixels with the origin at the top-left corner of the
+    /// window, is inside this viewport.
+    pub fn contains(&self, px: f32, py: f32, window_width: f32, window_height: f32) -> bool {
+        do self.data.with_borrow |d| {
+            let x = px / window_width;
+            let y = py / window_height;
+
+            x >= d.x && x < d.x + d.width && y >= d.y && y < d.y + d.height
+        }
+    }
+
+    /// Returns true if the pixel size of this viewport changed since the last call, i-e. if its
+    /// camera has to be notified.
+    #[doc(hidden)]
+    pub fn update_size(&mut self, width: f32, height: f32) -> bool {
+        do self.data.with_mut_borrow |d| {
+            if d.size != (width, height) {
+                d.size = (width, height);
+                true
+            }
+            else {
+                false
+            }
+        }
+    }
+}
+
+impl Eq for Viewport {
+    fn eq(&self, other: &Viewport) -> bool {
+        self.data.with_borrow(|d1| other.data.with_borrow(|d2| borrow::ref_eq(d1, d2)))
+    }
+}
@@ -6,25 +6,41 @@
 use glfw;
 use std::rt::io::timer::Timer;
 use std::num::Zero;
-use std::hashmap::HashMap;
+use std::vec;
+use std::util;
+use std::hashmap::{HashMap, HashSet};
 use std::rc::{RcMut, Rc};
 use extra::time;
+use extra::sort;
 use extra::arc::RWArc;
 use gl;
 use gl::types::*;
 use stb_image::image::*;
-use nalgebra::na::{Vec2, Vec3, Vec4};
+use nalgebra::na::{Vec2, Vec3, Vec4, Iso3};
 use nalgebra::na;
-use camera::{Camera, ArcBall};
-use object::Object;
+use camera::{Camera, ArcBall, CameraAnimation};
+use viewport::Viewport;
+use object;
+use object::{Object, ObjectRenderState, ALL_LAYERS};
+use skybox::Skybox;
+use background::{Background, BackgroundRenderer, UniformColor, Image};
+use instanced_object::InstancedObject;
+use instanced_object;
+use frustum::Frustum;
+use polyline::{Polyline, PolylineMode};
 use lines_manager::LinesManager;
+use overlay_manager::OverlayManager;
+use text_renderer::TextRenderer;
 use post_processing::post_processing_effect::PostProcessingEffect;
-use resources::shaders_manager::{ShadersManager, ObjectShader, LinesShader};
-use resources::textures_manager::Texture;
+use resources::shaders_manager::{ShadersManager, ObjectShader, LinesShader, PolylineShader,
+                                 OverlayShader, TextShader, InstancedObjectShader, SkyboxShader,
+                                 BackgroundShader, Other};
+use resources::textures_manager::{Texture, TextureParams};
 use resources::textures_manager;
 use resources::framebuffers_manager::{FramebuffersManager, RenderTarget};
 use builtins::loader;
 use event;
+use file_watcher::FileWatcher;
 use mesh::Mesh;
 use obj;
 
@@ -38,6 +54,16 @@ pub enum Light {
     StickToCamera
 }
 
+/// The plane a grid is drawn on.
+pub enum GridPlane {
+    /// The plane containing the `x` and `y` axis.
+    XYPlane,
+    /// The plane containing the `x` and `z` axis.
+    XZPlane,
+    /// The plane containing the `y` and `z` axis.
+    YZPlane
+}
+
 static DEFAULT_WIDTH: uint =  800u;
 static DEFAULT_HEIGHT: uint = 600u;
 
@@ -46,17 +72,32 @@ pub struct Window {
     priv window:                     glfw::Window,
     priv max_ms_per_frame:           Option<u64>,
     priv objects:                    ~[Object],
+    priv instanced_objects:          ~[InstancedObject],
     priv camera:                     @mut Camera,
+    priv viewports:                  ~[Viewport],
+    priv active_viewport:            Option<Viewport>,
+    priv pressed_buttons:            ~[glfw::MouseButton],
+    priv camera_animation:           Option<CameraAnimation>,
     priv light_mode:                 Light,
     priv wireframe_mode:             bool,
+    priv frustum_culling:            bool,
+    priv num_drawn_objects:          uint,
+    priv num_culled_objects:         uint,
     priv geometries:                 HashMap<~str, RcMut<Mesh>>,
-    priv background:                 Vec3<GLfloat>,
+    priv obj_paths:                  HashSet<~str>, // the geometries read from obj files
+    priv background:                 Background,
+    priv background_renderer:        BackgroundRenderer,
+    priv skybox:                     Option<Skybox>,
     priv lines_manager:              LinesManager,
+    priv overlay_manager:            OverlayManager,
+    priv text_renderer:              TextRenderer,
+    priv labels:                     ~[(~str, Vec3<f32>, f32, Vec3<f32>)],
     priv shaders_manager:            ShadersManager,
     priv framebuffers_manager:       FramebuffersManager,
     priv post_processing:            Option<@mut PostProcessingEffect>,
     priv post_process_render_target: RenderTarget,
-    priv events:                     RWArc<~[event::Event]>
+    priv events:                     RWArc<~[event::Event]>,
+    priv file_watcher:               Option<FileWatcher>
 }
 
 impl Window {
@@ -89,7 +130,7 @@ impl Window {
         self.camera
     }
 
-    /// The current camera.
+    /// The current camera. It is ignored while the window has viewports.
     pub fn set_camera(&mut self, camera: @mut Camera) {
         let (w, h) = self.window.get_size();
 
@@ -97,6 +138,17 @@ impl Window {
         self.camera.handle_event(&self.window, &event::FramebufferSize(w as f32, h as f32));
     }
 
+    /// Sets the animation moving a camera at the beginning of each frame. The animation is removed
+    /// once it is finished. `None` stops the current animation, leaving its camera where it is.
+    pub fn set_camera_animation(&mut self, animation: Option<CameraAnimation>) {
+        self.camera_animation = animation;
+    }
+
+    /// The current camera animation, if it is not finished yet.
+    pub fn camera_animation<'r>(&'r self) -> Option<&'r CameraAnimation> {
+        self.camera_animation.as_ref()
+    }
+
     /// Sets the maximum number of frames per second. Cannot be 0. `None` means there is no limit.
     pub fn set_framerate_limit(&mut self, fps: Option<u64>) {
         self.max_ms_per_frame = do fps.map |f| { assert!(f != 0); 1000 / f }
@@ -123,11 +175,61 @@ impl Window {
         self.wireframe_mode = mode;
     }
 
+    /// Switch on or off view-frustum culling. When set to `true` (the default), objects which
+    /// are entirely outside of the camera field of view are not sent to the gpu. Culling is
+    /// always disabled with cameras rendering the scene in several passes, e.g. stereo cameras.
+    pub fn set_frustum_culling(&mut self, culling: bool) {
+        self.frustum_culling = culling;
+    }
+
+    /// Number of objects drawn during the last rendering pass.
+    pub fn num_drawn_objects(&self) -> uint {
+        self.num_drawn_objects
+    }
+
+    /// Number of visible objects skipped by the frustum culling during the last rendering pass.
+    pub fn num_culled_objects(&self) -> uint {
+        self.num_culled_objects
+    }
+
     /// Sets the background color.
     pub fn set_background_color(&mut self, r: f32, g: GLfloat, b: f32) {
-        self.background.x = r;
-        self.background.y = g;
-        self.background.z = b;
+        self.background = UniformColor(Vec3::new(r, g, b));
+    }
+
+    /// Sets what is drawn behind the scene: a uniform color, a gradient, or an image. The
+    /// default is a black uniform color. Fails if the image of an `Image` background is a cube
+    /// map.
+    pub fn set_background(&mut self, background: Background) {
+        background.check();
+
+        self.background = background;
+    }
+
+    /// What is drawn behind the scene.
+    pub fn background(&self) -> Background {
+        self.background.clone()
+    }
+
+    /// Sets the skybox: a cube map drawn behind the scene, instead of the background color. It
+    /// is also the environment reflected by the objects (see `Object::set_reflectivity`). `None`
+    /// removes the skybox.
+    ///
+    /// Cube maps are loaded with `TexturesManager::add_cube_map` or
+    /// `TexturesManager::add_cube_map_from_panorama`. Fails if `cube_map` is a 2d texture.
+    pub fn set_skybox(&mut self, cube_map: Option<Rc<Texture>>) {
+        for t in cube_map.iter() {
+            if !t.borrow().is_cube_map() {
+                fail!("The skybox texture must be a cube map: use `TexturesManager::add_cube_map`.");
+            }
+        }
+
+        self.skybox = cube_map.map(|t| Skybox::new(t));
+    }
+
+    /// The cube map of the skybox, if any.
+    pub fn skybox(&self) -> Option<Rc<Texture>> {
+        self.skybox.as_ref().map(|s| s.texture().clone())
     }
 
     /// Adds a line to be drawn during the next frame.
@@ -135,17 +237,270 @@ impl Window {
         self.lines_manager.draw_line(a.clone(), b.clone(), color.clone());
     }
 
+    /// Draws the `x`, `y` and `z` axis (in red, green and blue respectively) during the next
+    /// frame.
+    ///
+    /// # Arguments
+    ///   * `origin` - the point the three axis start from
+    ///   * `length` - the length of each axis
+    pub fn draw_axes(&mut self, origin: &Vec3<f32>, length: f32) {
+        let x: Vec3<f32> = Vec3::x();
+        let y: Vec3<f32> = Vec3::y();
+        let z: Vec3<f32> = Vec3::z();
+
+        self.lines_manager.draw_line(origin.clone(), *origin + x * length, x);
+        self.lines_manager.draw_line(origin.clone(), *origin + y * length, y);
+        self.lines_manager.draw_line(origin.clone(), *origin + z * length, z);
+    }
+
+    /// Draws a grey square grid centered at the origin during the next frame.
+    ///
+    /// # Arguments
+    ///   * `plane` - the plane the grid lies on
+    ///   * `spacing` - the distance between two consecutive lines of the grid
+    ///   * `extent` - the half-width of the grid
+    pub fn draw_grid(&mut self, plane: GridPlane, spacing: f32, extent: f32) {
+        assert!(spacing > 0.0, "The grid spacing must be positive.");
+
+        let (u, v): (Vec3<f32>, Vec3<f32>) = match plane {
+            XYPlane => (Vec3::x(), Vec3::y()),
+            XZPlane => (Vec3::x(), Vec3::z()),
+            YZPlane => (Vec3::y(), Vec3::z())
+        };
+
+        let color = Vec3::new(0.5, 0.5, 0.5);
+        let n     = (extent / spacing) as int;
+
+        for i in range(-n, n + 1) {
+            let offset = (i as f32) * spacing;
+
+            self.lines_manager.draw_line(u * offset - v * extent, u * offset + v * extent, color);
+            self.lines_manager.draw_line(v * offset - u * extent, v * offset + u * extent, color);
+        }
+    }
+
+    /// Draws the edges of an axis-aligned box during the next frame.
+    pub fn draw_aabb(&mut self, mins: &Vec3<f32>, maxs: &Vec3<f32>, color: &Vec3<f32>) {
+        self.lines_manager.draw_aabb(mins, maxs, color);
+    }
+
+    /// Adds a 2d line to be drawn on top of the scene during the next frame. Coordinates are
+    /// given in pixels, with the origin at the top-left corner of the window.
+    pub fn draw_line_2d(&mut self, a: &Vec2<f32>, b: &Vec2<f32>, color: &Vec3<f32>) {
+        self.overlay_manager.draw_line(a, b, color);
+    }
+
+    /// Adds an axis-aligned rectangle to be drawn on top of the scene during the next frame.
+    /// Coordinates are given in pixels, with the origin at the top-left corner of the window.
+    ///
+    /// # Arguments
+    ///   * `a` - one corner of the rectangle
+    ///   * `b` - the corner opposite to `a`
+    ///   * `color` - the rectangle color
+    ///   * `filled` - if `false`, only the rectangle border is drawn
+    pub fn draw_rect(&mut self, a: &Vec2<f32>, b: &Vec2<f32>, color: &Vec3<f32>, filled: bool) {
+        let pts = [ a.clone(), Vec2::new(b.x, a.y), b.clone(), Vec2::new(a.x, b.y) ];
+
+        self.draw_polygon(pts, color, filled)
+    }
+
+    /// Adds a circle to be drawn on top of the scene during the next frame. Coordinates are given
+    /// in pixels, with the origin at the top-left corner of the window.
+    ///
+    /// # Arguments
+    ///   * `center` - the circle center
+    ///   * `radius` - the circle radius, in pixels
+    ///   * `color` - the circle color
+    ///   * `filled` - if `false`, only the circle border is drawn
+    pub fn draw_circle(&mut self, center: &Vec2<f32>, radius: f32, color: &Vec3<f32>, filled: bool) {
+        let _2pi: f32 = Real::two_pi();
+        let nsubdivs  = if radius < 16.0 { 16u } else if radius > 256.0 { 256u } else { radius as uint };
+        let mut pts   = vec::with_capacity(nsubdivs);
+
+        for i in range(0u, nsubdivs) {
+            let ang = _2pi * (i as f32) / (nsubdivs as f32);
+
+            pts.push(Vec2::new(center.x + radius * ang.cos(), center.y + radius * ang.sin()));
+        }
+
+        self.draw_polygon(pts, color, filled)
+    }
+
+    /// Adds a polygon to be drawn on top of the scene during the next frame. Coordinates are given
+    /// in pixels, with the origin at the top-left corner of the window.
+    ///
+    /// # Arguments
+    ///   * `points` - the polygon vertices. The polygon must be convex if it is filled
+    ///   * `color` - the polygon color
+    ///   * `filled` - if `false`, only the polygon border is drawn
+    pub fn draw_polygon(&mut self, points: &[Vec2<f32>], color: &Vec3<f32>, filled: bool) {
+        if filled {
+            self.overlay_manager.draw_filled_polygon(points, color)
+        }
+        else {
+            self.overlay_manager.draw_polygon_border(points, color)
+        }
+    }
+
+    /// Adds some text to be drawn on top of the scene during the next frame. Coordinates are given
+    /// in pixels, with the origin at the top-left corner of the window.
+    ///
+    /// # Arguments
+    ///   * `text` - the text to draw. It may contain several lines separated by `\n`. Non-ascii
+    ///   characters are displayed as `?`
+    ///   * `pos` - the top-left corner of the text
+    ///   * `size` - the height of one line of text, in pixels
+    ///   * `color` - the text color
+    pub fn draw_text(&mut self, text: &str, pos: &Vec2<f32>, size: f32, color: &Vec3<f32>) {
+        self.text_renderer.draw_text(text, pos, size, color);
+    }
+
+    /// Adds some text anchored to a 3d point, to be drawn on top of the scene during the next
+    /// frame. Nothing is drawn if the point is behind the camera or out of the view. With
+    /// viewports, the text is drawn in every viewport showing the point, as seen from the camera
+    /// of the viewport.
+    ///
+    /// # Arguments
+    ///   * `text` - the text to draw
+    ///   * `world_pos` - the 3d point the top-left corner of the text is attached to
+    ///   * `size` - the height of one line of text, in pixels
+    ///   * `color` - the text color
+    pub fn draw_label(&mut self, text: &str, world_pos: &Vec3<f32>, size: f32, color: &Vec3<f32>) {
+        // the labels are projected once the cameras are up to date, see `draw_labels`
+        self.labels.push((text.to_owned(), world_pos.clone(), size, color.clone()));
+    }
+
+    /// Adds a persistent polyline to the scene. Contrary to `draw_line`, the polyline is kept
+    /// (and drawn) until it is removed with `remove_polyline`.
+    ///
+    /// # Arguments
+    ///   * `points` - the polyline vertices
+    ///   * `mode` - the way the vertices are linked together
+    pub fn add_polyline(&mut self, points: ~[Vec3<f32>], mode: PolylineMode) -> Polyline {
+        let res = Polyline::new(points, mode, 1.0, 1.0, 1.0);
+
+        self.lines_manager.add_polyline(res.clone());
+
+        res
+    }
+
+    /// Removes a polyline from the scene.
+    pub fn remove_polyline(&mut self, polyline: Polyline) {
+        self.lines_manager.remove_polyline(&polyline)
+    }
+
+    /// The list of persistent polylines on the scene.
+    pub fn polylines<'r>(&'r self) -> &'r [Polyline] {
+        self.lines_manager.polylines()
+    }
+
+    /// Adds a viewport showing the scene seen from `camera`. Once the window has at least one
+    /// viewport, only the viewports are rendered: the window camera is not used anymore. Viewports
+    /// are drawn in the order they were added, so the last one is on top if they overlap.
+    ///
+    /// The inputs are sent to the camera of the viewport under the cursor.
+    ///
+    /// # Arguments
+    ///   * `camera` - the camera of the viewport
+    ///   * `x` - the left side of the viewport, in fraction of the window width
+    ///   * `y` - the top side of the viewport, in fraction of the window height
+    ///   * `width` - the viewport width, in fraction of the window width
+    ///   * `height` - the viewport height, in fraction of the window height
+    pub fn add_viewport(&mut self, camera: @mut Camera, x: f32, y: f32, width: f32, height: f32) -> Viewport {
+        let res = Viewport::new(camera, x, y, width, height);
+
+        self.viewports.push(res.clone());
+
+        if self.active_viewport.is_none() {
+            self.active_viewport = Some(res.clone());
+        }
+
+        res
+    }
+
+    /// Removes a viewport. The window camera is used again once all viewports are removed.
+    pub fn remove_viewport(&mut self, viewport: Viewport) {
+        match self.viewports.iter().position(|v| viewport == *v) {
+            Some(i) => { self.viewports.remove(i); },
+            None    => { }
+        }
+
+        if self.active_viewport == Some(viewport) {
+            self.active_viewport = self.viewports.head_opt().map(|v| v.clone());
+        }
+    }
+
+    /// Removes all the viewports. The window camera is used again.
+    pub fn clear_viewports(&mut self) {
+        self.viewports.clear();
+        self.active_viewport = None;
+    }
+
+    /// The list of viewports, in drawing order.
+    pub fn viewports<'r>(&'r self) -> &'r [Viewport] {
+        let res: &'r [Viewport] = self.viewports;
+
+        res
+    }
+
+    /// The viewport receiving the inputs, i-e. the last viewport hovered by the cursor.
+    pub fn active_viewport(&self) -> Option<Viewport> {
+        self.active_viewport.clone()
+    }
+
     /// Removes an object from the scene.
     pub fn remove(&mut self, o: Object) {
         match self.objects.iter().rposition(|e| o == *e) {
             Some(i) => {
-                // XXX: release textures and buffers if nobody else use them
+                // the mesh buffers are released with the last handle of the mesh. The textures
+                // stay in the textures cache until `release_unused_textures` is called.
                 self.objects.swap_remove(i);
             },
             None => { }
         }
     }
 
+    /// Removes from the textures cache all the textures not used by the objects of the scene.
+    /// They are released from the gpu as soon as no object uses them anymore. Returns the
+    /// number of textures removed from the cache.
+    ///
+    /// Call this after removing objects or replacing their textures to avoid keeping unused
+    /// textures on the gpu. Textures kept for later use, outside of the scene, must be pinned
+    /// first (see `TexturesManager::pin`): otherwise, adding them again would load a second
+    /// copy.
+    pub fn release_unused_textures(&mut self) -> uint {
+        let mut used = HashSet::new();
+
+        for o in self.objects.iter() {
+            for t in o.textures().iter() {
+                used.insert(t.borrow().id());
+            }
+        }
+
+        for o in self.instanced_objects.iter() {
+            used.insert(o.texture().borrow().id());
+        }
+
+        match self.skybox {
+            Some(ref skybox) => { used.insert(skybox.texture().borrow().id()); },
+            None             => { }
+        }
+
+        match self.background {
+            Image(ref texture, _) => { used.insert(texture.borrow().id()); },
+            _                     => { }
+        }
+
+        for v in self.viewports.iter() {
+            match v.background() {
+                Some(Image(ref texture, _)) => { used.insert(texture.borrow().id()); },
+                _                           => { }
+            }
+        }
+
+        textures_manager::singleton().release_unused(&used)
+    }
+
     /// Adds an obj model to the scene.
     ///
     /// # Arguments
@@ -167,6 +522,7 @@ impl Window {
                 };
 
             if insert {
+                self.obj_paths.insert(key.clone());
                 self.geometries.insert(key, mesh.clone());
             }
 
@@ -182,6 +538,28 @@ impl Window {
         res
     }
 
+    /// Adds a user-defined mesh to the scene.
+    ///
+    /// # Arguments
+    ///     * `mesh`  - the mesh to display. It is not shared with any other object.
+    ///     * `scale` - uniform scale to apply to the mesh.
+    pub fn add_mesh(&mut self, mesh: Mesh, scale: GLfloat) -> Object {
+        // FIXME: this weird block indirection are here because of Rust issue #6248
+        let res = {
+            let tex = textures_manager::singleton().get("default").unwrap();
+
+            Object::new(
+                RcMut::from_send(mesh),
+                1.0, 1.0, 1.0,
+                tex,
+                scale, scale, scale)
+        };
+
+        self.objects.push(res.clone());
+
+        res
+    }
+
     /// Adds a cube to the scene. The cube is initially axis-aligned and centered at (0, 0, 0).
     ///
     /// # Arguments
@@ -295,6 +673,89 @@ impl Window {
         res
     }
 
+    /// Adds a set of cubes drawn with a single draw call. Use this instead of `add_cube` to display
+    /// thousands of cubes. All the cubes share the same extents and texture, but each one has its
+    /// own transformation and color.
+    ///
+    /// Instancing requires opengl 3.3 or the `GL_ARB_instanced_arrays` extension: this fails
+    /// otherwise (see `instanced_object::is_supported`).
+    ///
+    /// # Arguments
+    ///   * `wx` - the cubes extent along the z axis
+    ///   * `wy` - the cubes extent along the y axis
+    ///   * `wz` - the cubes extent along the z axis
+    ///   * `transforms` - the transformation of each cube
+    ///   * `colors` - the color of each cube. There must be exactly one color per transformation
+    pub fn add_instanced_cubes(&mut self,
+                               wx:         GLfloat,
+                               wy:         GLfloat,
+                               wz:         GLfloat,
+                               transforms: ~[Iso3<f32>],
+                               colors:     ~[Vec3<f32>])
+                               -> InstancedObject {
+        self.add_instanced_geometry("cube", transforms, colors, wx, wy, wz)
+    }
+
+    /// Adds a set of spheres drawn with a single draw call. Use this instead of `add_sphere` to
+    /// display thousands of spheres. All the spheres share the same radius and texture, but each
+    /// one has its own transformation and color.
+    ///
+    /// Instancing requires opengl 3.3 or the `GL_ARB_instanced_arrays` extension: this fails
+    /// otherwise (see `instanced_object::is_supported`).
+    ///
+    /// # Arguments
+    ///   * `r` - the spheres radius
+    ///   * `transforms` - the transformation of each sphere
+    ///   * `colors` - the color of each sphere. There must be exactly one color per transformation
+    pub fn add_instanced_spheres(&mut self,
+                                 r:          GLfloat,
+                                 transforms: ~[Iso3<f32>],
+                                 colors:     ~[Vec3<f32>])
+                                 -> InstancedObject {
+        self.add_instanced_geometry("sphere", transforms, colors, r / 0.5, r / 0.5, r / 0.5)
+    }
+
+    fn add_instanced_geometry(&mut self,
+                              name:       &str,
+                              transforms: ~[Iso3<f32>],
+                              colors:     ~[Vec3<f32>],
+                              sx:         GLfloat,
+                              sy:         GLfloat,
+                              sz:         GLfloat)
+                              -> InstancedObject {
+        if !instanced_object::is_supported() {
+            fail!("Instancing requires opengl 3.3 or the GL_ARB_instanced_arrays extension.");
+        }
+
+        // FIXME: this weird block indirection are here because of Rust issue #6248
+        let res = {
+            let tex  = textures_manager::singleton().get("default").unwrap();
+            let geom = self.geometries.find(&name.to_owned()).unwrap();
+            InstancedObject::new(geom.clone(), transforms, colors, tex, sx, sy, sz)
+        };
+
+        self.instanced_objects.push(res.clone());
+
+        res
+    }
+
+    /// Removes a set of instances from the scene.
+    pub fn remove_instanced(&mut self, o: InstancedObject) {
+        match self.instanced_objects.iter().rposition(|e| o == *e) {
+            Some(i) => {
+                self.instanced_objects.swap_remove(i);
+            },
+            None => { }
+        }
+    }
+
+    /// The list of instanced objects on the scene.
+    pub fn instanced_objects<'r>(&'r self) -> &'r [InstancedObject] {
+        let res: &'r [InstancedObject] = self.instanced_objects;
+
+        res
+    }
+
     /// Adds a double-sided quad to the scene. The cylinder is initially centered at (0, 0, 0). The
     /// quad itself is composed of a user-defined number of triangles regularly spaced on a grid.
     /// This is the main way to draw height maps.
@@ -379,7 +840,7 @@ impl Window {
 
     #[doc(hidden)]
     pub fn add_texture(&mut self, path: &str) -> Rc<Texture> {
-        textures_manager::singleton().add(path)
+        textures_manager::singleton().add(path, &TextureParams::new())
     }
 
     /// Converts a 3d point to 2d screen coordinates.
@@ -397,17 +858,29 @@ impl Window {
     }
 
     /// Converts a point in 2d screen coordinates to a ray (a 3d position and a direction).
+    /// The ray starts on the near clipping plane. With an orthographic camera, all rays are
+    /// parallel. With viewports, the ray is cast by the camera of the viewport under the point.
     pub fn unproject(&self, window_coord: &Vec2<f32>) -> (Vec3<f32>, Vec3<f32>) {
-        let (w, h) = self.window.get_size();
+        let w = self.width();
+        let h = self.height();
+
+        let (camera, x, y, width, height) = match self.viewport_at(window_coord.x, window_coord.y) {
+            Some(viewport) => {
+                let (vx, vy, vw, vh) = viewport.rect();
+
+                (viewport.camera(), vx * w, vy * h, vw * w, vh * h)
+            },
+            None => (self.camera, 0.0, 0.0, w, h)
+        };
 
         let normalized_coord = Vec2::new(
-            2.0 * window_coord.x  / (w as f32) - 1.0,
-            2.0 * -window_coord.y / (h as f32) + 1.0);
+            2.0 * (window_coord.x - x)  / width - 1.0,
+            2.0 * -(window_coord.y - y) / height + 1.0);
 
         let normalized_begin = Vec4::new(normalized_coord.x, normalized_coord.y, -1.0, 1.0);
         let normalized_end   = Vec4::new(normalized_coord.x, normalized_coord.y, 1.0, 1.0);
 
-        let cam = self.camera.inv_transformation();
+        let cam = camera.inv_transformation();
 
         let h_unprojected_begin = cam * normalized_begin;
         let h_unprojected_end   = cam * normalized_end;
@@ -418,6 +891,35 @@ impl Window {
         (unprojected_begin, na::normalize(&(unprojected_end - unprojected_begin)))
     }
 
+    /// Moves the camera such that all the visible objects fill the view, keeping its view
+    /// direction. With viewports, the camera of the active viewport is moved. Nothing happens if
+    /// no object is visible. Instanced objects are not taken into account.
+    pub fn frame_scene(&mut self) {
+        let mut aabb = None;
+
+        for o in self.objects.iter() {
+            if o.visible() {
+                let object_aabb = o.world_aabb();
+
+                aabb = Some(match aabb {
+                    Some(a) => object_aabb.merged(&a),
+                    None    => object_aabb
+                });
+            }
+        }
+
+        match aabb {
+            Some(a) => self.input_camera().frame(&a),
+            None    => { }
+        }
+    }
+
+    /// Moves the camera such that an object fills the view, keeping its view direction. With
+    /// viewports, the camera of the active viewport is moved.
+    pub fn frame_object(&mut self, object: &Object) {
+        self.input_camera().frame(&object.world_aabb())
+    }
+
     /// The list of objects on the scene.
     pub fn objects<'r>(&'r self) -> &'r [Object] {
         let res: &'r [Object] = self.objects;
@@ -442,6 +944,16 @@ impl Window {
         let events = self.events.clone();
         do events.read |es| {
             for e in es.iter() {
+                match *e {
+                    event::ButtonPressed(button, _) => {
+                        if !self.pressed_buttons.contains(&button) {
+                            self.pressed_buttons.push(button);
+                        }
+                    },
+                    event::ButtonReleased(button, _) => self.pressed_buttons.retain(|b| *b != button),
+                    _ => { }
+                }
+
                 if events_handler(self, e) {
                     match *e {
                         event::KeyReleased(key) => {
@@ -452,11 +964,17 @@ impl Window {
                         },
                         event::FramebufferSize(w, h) => {
                             self.update_viewport(w, h);
+                            // viewport cameras are notified of their own size before being rendered
+                            self.camera.handle_event(&self.window, e);
+                            continue
+                        },
+                        event::CursorPos(x, y) => {
+                            self.update_active_viewport(x, y);
                         },
                         _ => { }
                     }
 
-                    self.camera.handle_event(&self.window, e);
+                    self.input_camera().handle_event(&self.window, e);
                 }
             }
         }
@@ -472,6 +990,8 @@ impl Window {
         let mut curr  = time::precise_time_ns();
 
         while !self.window.should_close() {
+            self.reload_modified_files();
+
             // collect events
             glfw::poll_events();
 
@@ -483,6 +1003,138 @@ impl Window {
         }
     }
 
+    /// Enables hot reloading. The obj files loaded with `add_obj`, the textures read from files,
+    /// and the shaders of the post-processing effect are checked for modifications every
+    /// `period_ms` milliseconds, and reloaded in place: the objects using them are updated
+    /// without having to be re-created.
+    ///
+    /// The levels of detail generated from a reloaded mesh are not updated.
+    pub fn enable_hot_reloading(&mut self, period_ms: u64) {
+        self.file_watcher = Some(FileWatcher::new(period_ms));
+    }
+
+    /// Disables hot reloading.
+    pub fn disable_hot_reloading(&mut self) {
+        self.file_watcher = None;
+    }
+
+    fn reload_modified_files(&mut self) {
+        let modified = match self.file_watcher {
+            None              => return,
+            Some(ref mut fw) => {
+                if !fw.due() {
+                    return
+                }
+
+                // the files loaded since the last check are watched too. The built-in
+                // geometries are not watched: a file with the same name as one of them must not
+                // replace it.
+                for path in self.obj_paths.iter() {
+                    fw.watch(*path);
+                }
+
+                for path in textures_manager::singleton().file_paths().iter() {
+                    fw.watch(*path);
+                }
+
+                match self.post_processing {
+                    Some(ref p) => {
+                        for path in p.shader_files().iter() {
+                            fw.watch(*path);
+                        }
+                    },
+                    None => { }
+                }
+
+                fw.poll()
+            }
+        };
+
+        for path in modified.iter() {
+            // the files might be saved while incomplete: keep the previous assets until the
+            // errors are fixed
+            match self.geometries.find(path) {
+                Some(mesh) if self.obj_paths.contains(path) => {
+                    match obj::try_parse_file(*path) {
+                        Ok(new_mesh) => {
+                            let mut new_mesh = new_mesh;
+
+                            mesh.with_mut_borrow(|m| util::swap(m, &mut new_mesh));
+                        },
+                        Err(err) => println("Failed to reload the mesh " + *path + ": " + err)
+                    }
+                },
+                _ => { }
+            }
+
+            match textures_manager::singleton().reload(*path) {
+                Some(Err(err)) => println("Failed to reload the texture: " + err),
+                _              => { }
+            }
+
+            match self.post_processing {
+                Some(ref p) => {
+                    if p.shader_files().contains(path) {
+                        p.reload_shaders();
+                    }
+                },
+                None => { }
+            }
+        }
+    }
+
+    /// Renders the scene seen from `camera` into an offscreen render target, created with
+    /// `FramebuffersManager::new_render_target`. The result can then be applied to objects with
+    /// `Object::set_texture_slot("diffuse", target.texture().unwrap())`, e.g. to display a
+    /// security camera monitor or a mirror.
+    ///
+    /// This is meant to be called from the render loop callback, once per frame. The projection
+    /// of `camera` is adapted to the size of the target, and only the first pass of multi-pass
+    /// cameras is rendered. Only the objects belonging to at least one of the `layers` are
+    /// rendered (see `Object::set_layers`): the objects textured with `target` must be excluded
+    /// since a texture cannot be read and written at the same time. The lines drawn so far during
+    /// this frame are rendered too, and are still displayed on the window afterwards.
+    pub fn render_to_texture(&mut self, target: &RenderTarget, camera: @mut Camera, layers: u32) {
+        let (w, h) = match target.size() {
+            Some(size) => size,
+            None       => fail!("Cannot render to texture: the render target must be offscreen.")
+        };
+
+        // the user might have changed the current program
+        self.shaders_manager.select(Other);
+
+        camera.handle_event(&self.window, &event::FramebufferSize(w as f32, h as f32));
+
+        let mut camera = camera;
+        util::swap(&mut self.camera, &mut camera);
+
+        match self.light_mode {
+            StickToCamera => self.set_light(StickToCamera),
+            _             => { }
+        }
+
+        self.framebuffers_manager.select(target);
+        verify!(gl::Viewport(0, 0, w as GLint, h as GLint));
+        verify!(gl::Scissor(0, 0, w as GLint, h as GLint));
+
+        self.upload_camera(0);
+        self.render_scene(layers);
+
+        util::swap(&mut self.camera, &mut camera);
+
+        match self.light_mode {
+            StickToCamera => self.set_light(StickToCamera),
+            _             => { }
+        }
+
+        let ww = self.width();
+        let wh = self.height();
+
+        self.framebuffers_manager.select(&FramebuffersManager::screen());
+        verify!(gl::Viewport(0, 0, ww as GLint, wh as GLint));
+        verify!(gl::Scissor(0, 0, ww as GLint, wh as GLint));
+    }
+
     /// Sets the light mode. Only one light is supported.
     pub fn set_light(&mut self, pos: Light) {
         match pos {
@@ -499,6 +1151,8 @@ impl Window {
     fn set_light_pos(&mut self, pos: &Vec3<GLfloat>) {
         self.shaders_manager.select(ObjectShader);
         verify!(gl::Uniform3f(self.shaders_manager.object_context().light, pos.x, pos.y, pos.z));
+        self.shaders_manager.select(InstancedObjectShader);
+        verify!(gl::Uniform3f(self.shaders_manager.instanced_object_context().light, pos.x, pos.y, pos.z));
         // FIXME: select the LinesShader too ?
     }
 
@@ -561,17 +1215,32 @@ impl Window {
                 max_ms_per_frame:      None,
                 window:                window,
                 objects:               ~[],
+                instanced_objects:     ~[],
                 camera:                camera as @mut Camera,
+                viewports:             ~[],
+                active_viewport:       None,
+                pressed_buttons:       ~[],
+                camera_animation:      None,
                 light_mode:            Absolute(Vec3::new(0.0, 10.0, 0.0)),
                 wireframe_mode:        false,
+                frustum_culling:       true,
+                num_drawn_objects:     0,
+                num_culled_objects:    0,
                 geometries:            builtins,
-                background:            Vec3::new(0.0, 0.0, 0.0),
+                obj_paths:             HashSet::new(),
+                background:            UniformColor(Vec3::new(0.0, 0.0, 0.0)),
+                background_renderer:   BackgroundRenderer::new(),
+                skybox:                None,
                 lines_manager:         LinesManager::new(),
+                overlay_manager:       OverlayManager::new(),
+                text_renderer:         TextRenderer::new(),
+                labels:                ~[],
                 shaders_manager:       shaders,
                 post_processing:       None,
                 post_process_render_target: FramebuffersManager::new_render_target(width, height),
                 framebuffers_manager:  FramebuffersManager::new(),
-                events:                RWArc::new(~[])
+                events:                RWArc::new(~[]),
+                file_watcher:          None
             };
 
             // setup callbacks
@@ -627,7 +1296,23 @@ impl Window {
     }
 
     fn draw(&mut self, curr: &mut u64, timer: &mut Timer) {
-        self.camera.update(&self.window);
+        // the user might have changed the current program (e.g. by creating a post-processing
+        // effect) so the program tracked by the shaders manager cannot be trusted anymore.
+        self.shaders_manager.select(Other);
+
+        self.input_camera().update(&self.window);
+
+        let animation_finished = match self.camera_animation {
+            Some(ref mut animation) => {
+                animation.update();
+                animation.is_finished()
+            },
+            None => false
+        };
+
+        if animation_finished {
+            self.camera_animation = None;
+        }
 
         match self.light_mode {
             StickToCamera => self.set_light(StickToCamera),
@@ -642,21 +1327,32 @@ impl Window {
             self.framebuffers_manager.select(&FramebuffersManager::screen());
         }
 
-        // TODO: change to pass_iter when I learn the lingo
-        for pass in range(0u, self.camera.num_passes()) {
-            self.camera.start_pass(pass, &self.window);
-
-            self.shaders_manager.select(LinesShader);
-            let view_location2 = self.shaders_manager.lines_context().view;
-            self.camera.upload(pass, view_location2);
+        // bounding boxes are lines: they must be registered before the scene is rendered
+        for o in self.objects.iter() {
+            if o.visible() && o.bounding_box_shown() {
+                let aabb = o.world_aabb();
 
-            self.shaders_manager.select(ObjectShader);
-            let view_location1 = self.shaders_manager.object_context().view;
-            self.camera.upload(pass, view_location1);
+                self.lines_manager.draw_aabb(aabb.mins(), aabb.maxs(), &Vec3::new(1.0, 1.0, 0.0));
+            }
+        }
 
-            self.render_scene();
+        if self.viewports.is_empty() {
+            // TODO: change to pass_iter when I learn the lingo
+            for pass in range(0u, self.camera.num_passes()) {
+                self.camera.start_pass(pass, &self.window);
+                self.upload_camera(pass);
+                self.render_scene(ALL_LAYERS);
+            }
+            self.camera.render_complete(&self.window);
         }
-        self.camera.render_complete(&self.window);
+        else {
+            self.render_viewports();
+        }
+
+        // the lines are drawn by every viewport and pass: they can only be removed now
+        self.lines_manager.clear();
+
+        self.draw_labels();
 
         let w = self.width();
         let h = self.height();
@@ -679,6 +1375,19 @@ impl Window {
             None => { }
         }
 
+        // draw the 2d overlay on top of everything
+        if self.overlay_manager.needs_rendering() {
+            verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL));
+            self.shaders_manager.select(OverlayShader);
+            self.overlay_manager.upload(self.shaders_manager.overlay_context(), w, h);
+        }
+
+        if self.text_renderer.needs_rendering() {
+            verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL));
+            self.shaders_manager.select(TextShader);
+            self.text_renderer.upload(self.shaders_manager.text_context(), w, h);
+        }
+
         // We are done: swap buffers
         self.window.swap_buffers();
 
@@ -699,20 +1408,198 @@ impl Window {
         // self.opaque_objects.clear();
     }
 
-    fn render_scene(&mut self) {
+    // Uploads the view transformation of the current camera to all the shaders.
+    fn upload_camera(&mut self, pass: uint) {
+        self.shaders_manager.select(LinesShader);
+        let view_location2 = self.shaders_manager.lines_context().view;
+        self.camera.upload(pass, view_location2);
+
+        self.shaders_manager.select(PolylineShader);
+        let view_location3 = self.shaders_manager.polyline_context().view;
+        self.camera.upload(pass, view_location3);
+
+        self.shaders_manager.select(InstancedObjectShader);
+        let view_location4 = self.shaders_manager.instanced_object_context().view;
+        self.camera.upload(pass, view_location4);
+
+        self.shaders_manager.select(ObjectShader);
+        let view_location1 = self.shaders_manager.object_context().view;
+        self.camera.upload(pass, view_location1);
+    }
+
+    // Renders each viewport with its own camera, background and layer mask. Only the first pass
+    // of multi-pass cameras is rendered.
+    fn render_viewports(&mut self) {
+        let w         = self.width();
+        let h         = self.height();
+        let viewports = self.viewports.clone();
+        let mut ndrawn  = 0u;
+        let mut nculled = 0u;
+
+        for viewport in viewports.iter() {
+            let mut viewport = viewport.clone();
+            let (x, y, vw, vh) = viewport.pixel_rect(w, h);
+
+            if vw <= 0 || vh <= 0 {
+                continue
+            }
+
+            let mut camera = viewport.camera();
+
+            if viewport.update_size(vw as f32, vh as f32) {
+                camera.handle_event(&self.window, &event::FramebufferSize(vw as f32, vh as f32));
+            }
+
+            let mut background = match viewport.background() {
+                Some(b) => b,
+                None    => self.background.clone()
+            };
+
+            util::swap(&mut self.camera, &mut camera);
+            util::swap(&mut self.background, &mut background);
+
+            match self.light_mode {
+                StickToCamera => self.set_light(StickToCamera),
+                _             => { }
+            }
+
+            verify!(gl::Viewport(x, y, vw, vh));
+            verify!(gl::Scissor(x, y, vw, vh));
+
+            self.upload_camera(0);
+            self.render_scene(viewport.layers());
+
+            ndrawn  = ndrawn + self.num_drawn_objects;
+            nculled = nculled + self.num_culled_objects;
+
+            util::swap(&mut self.camera, &mut camera);
+            util::swap(&mut self.background, &mut background);
+        }
+
+        self.num_drawn_objects  = ndrawn;
+        self.num_culled_objects = nculled;
+
+        verify!(gl::Viewport(0, 0, w as GLint, h as GLint));
+        verify!(gl::Scissor(0, 0, w as GLint, h as GLint));
+    }
+
+    // The camera receiving the inputs: the one of the active viewport if there is one.
+    fn input_camera(&self) -> @mut Camera {
+        match self.active_viewport {
+            Some(ref v) => v.camera(),
+            None        => self.camera
+        }
+    }
+
+    // Selects the viewport under the cursor as the one receiving the inputs. The selection does
+    // not change while any mouse button is pressed, so that dragging out of a viewport keeps
+    // moving its camera whatever its bindings are.
+    fn update_active_viewport(&mut self, x: f32, y: f32) {
+        if self.viewports.is_empty() || !self.pressed_buttons.is_empty() {
+            return
+        }
+
+        match self.viewport_at(x, y) {
+            Some(viewport) => self.active_viewport = Some(viewport),
+            None           => { }
+        }
+    }
+
+    // Projects the labels added with `draw_label` with the camera of every viewport, and gives
+    // them to the text renderer.
+    fn draw_labels(&mut self) {
+        let mut labels = ~[];
+        util::swap(&mut labels, &mut self.labels);
+
+        let w = self.width();
+        let h = self.height();
+
+        // the cameras with their rectangle, in pixels with the origin at the top-left corner
+        let views = if self.viewports.is_empty() {
+            ~[ (self.camera, 0.0, 0.0, w, h) ]
+        }
+        else {
+            do self.viewports.map |v| {
+                let (vx, vy, vw, vh) = v.rect();
+
+                (v.camera(), vx * w, vy * h, vw * w, vh * h)
+            }
+        };
+
+        for &(ref text, ref world_pos, size, ref color) in labels.iter() {
+            let h_world_pos: Vec4<f32> = na::to_homogeneous(world_pos);
+
+            for &(camera, x, y, width, height) in views.iter() {
+                let h_projected = camera.transformation() * h_world_pos;
+
+                if h_projected.w > 0.0 {
+                    let projected: Vec3<f32> = na::from_homogeneous(&h_projected);
+
+                    // a point out of a viewport would be labeled over its neighbours
+                    if projected.x.abs() <= 1.0 && projected.y.abs() <= 1.0 {
+                        let pos = Vec2::new(x + (1.0 + projected.x) * width / 2.0,
+                                            y + (1.0 - projected.y) * height / 2.0);
+
+                        self.text_renderer.draw_text(*text, &pos, size, color);
+                    }
+                }
+            }
+        }
+    }
+
+    // The viewport under a point, in pixels with the origin at the top-left corner of the window.
+    fn viewport_at(&self, x: f32, y: f32) -> Option<Viewport> {
+        let w = self.width();
+        let h = self.height();
+
+        // the last viewports are drawn on top
+        self.viewports.iter().rposition(|v| v.contains(x, y, w, h)).map(|i| self.viewports[i].clone())
+    }
+
+    // Renders the objects belonging to at least one of the `layers`.
+    fn render_scene(&mut self, layers: u32) {
         // Activate the default texture
         verify!(gl::ActiveTexture(gl::TEXTURE0));
-        // Clear the screen to black
-        verify!(gl::ClearColor(self.background.x, self.background.y, self.background.z, 1.0));
+        // Clear the screen to the background color
+        let clear_color = match self.background {
+            UniformColor(c) => c,
+            _               => Vec3::new(0.0, 0.0, 0.0)
+        };
+
+        verify!(gl::ClearColor(clear_color.x, clear_color.y, clear_color.z, 1.0));
         verify!(gl::Clear(gl::COLOR_BUFFER_BIT));
         verify!(gl::Clear(gl::DEPTH_BUFFER_BIT));
 
+        if self.background_renderer.needs_rendering(&self.background) {
+            self.shaders_manager.select(BackgroundShader);
+            self.background_renderer.upload(self.shaders_manager.background_context(), &self.background);
+            self.shaders_manager.select(ObjectShader);
+        }
+
+        match self.skybox {
+            Some(ref skybox) => {
+                let inv_proj_view = self.camera.inv_transformation();
+                let eye           = self.camera.eye();
+
+                self.shaders_manager.select(SkyboxShader);
+                skybox.upload(self.shaders_manager.skybox_context(), &inv_proj_view, &eye);
+                self.shaders_manager.select(ObjectShader);
+            },
+            None => { }
+        }
+
         if self.lines_manager.needs_rendering() {
             self.shaders_manager.select(LinesShader);
             self.lines_manager.upload(self.shaders_manager.lines_context());
             self.shaders_manager.select(ObjectShader);
         }
 
+        if self.lines_manager.has_polylines() {
+            self.shaders_manager.select(PolylineShader);
+            self.lines_manager.upload_polylines(self.shaders_manager.polyline_context());
+            self.shaders_manager.select(ObjectShader);
+        }
+
         if self.wireframe_mode {
             verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE));
         }
@@ -720,8 +1607,66 @@ impl Window {
             verify!(gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL));
         }
 
-        for o in self.objects.iter() {
-            o.upload(self.shaders_manager.object_context())
+        // The frustum of multi-pass cameras, e.g. stereo cameras, encloses the frustum of each
+        // pass only roughly: culling is disabled for them so that nothing visible is skipped.
+        let culling     = self.frustum_culling && self.camera.num_passes() == 1;
+        let frustum     = Frustum::from_transformation(&self.camera.transformation());
+        let eye         = self.camera.eye();
+        let mut nculled = 0u;
+        let mut queue   = vec::with_capacity(self.objects.len());
+
+        for (i, o) in self.objects.iter().enumerate() {
+            if !o.visible() || o.layers() & layers == 0 {
+                continue
+            }
+
+            // test the bounding sphere first since it is cheaper
+            if culling &&
+               (!frustum.intersects_sphere(&o.world_bounding_sphere()) ||
+                !frustum.intersects_aabb(&o.world_aabb())) {
+                nculled = nculled + 1;
+                continue
+            }
+
+            o.select_lod(&eye);
+
+            let (mesh, texture) = o.render_key();
+            queue.push((mesh, texture, i));
+        }
+
+        // all objects use the same shader: sorting by mesh and then by texture minimizes the
+        // number of buffers and textures switches.
+        sort::quick_sort(queue, |a, b| a <= b);
+
+        self.shaders_manager.select(ObjectShader);
+        verify!(gl::Uniform3f(self.shaders_manager.object_context().eye, eye.x, eye.y, eye.z));
+
+        let environment = match self.skybox {
+            Some(ref skybox) => skybox.texture().borrow().id(),
+            None             => 0
+        };
+
+        object::bind_environment_map(self.shaders_manager.object_context(), environment);
+
+        let mut state = ObjectRenderState::new();
+
+        for &(_, _, i) in queue.iter() {
+            self.objects[i].upload(self.shaders_manager.object_context(), &mut state);
+        }
+
+        state.unbind();
+
+        self.num_drawn_objects  = queue.len();
+        self.num_culled_objects = nculled;
+
+        if !self.instanced_objects.is_empty() {
+            self.shaders_manager.select(InstancedObjectShader);
+
+            for o in self.instanced_objects.iter() {
+                o.upload(self.shaders_manager.instanced_object_context());
+            }
+
+            self.shaders_manager.select(ObjectShader);
         }
     }
 